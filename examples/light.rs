@@ -1,17 +1,17 @@
 use std::{thread::sleep, time::Duration};
 
-use dualsense_rs::DualSense;
+use dualsense_rs::{properties::animation::LightbarAnimation, DualSense};
 
 fn main() {
     let mut controller = DualSense::default();
 
     let _handle = controller.run();
-    controller.set_light_red(128);
+    controller.play_lightbar_animation(LightbarAnimation::rainbow(6.0));
 
     let mut idx = 0;
     loop {
         println!("Light for player {idx}");
-        controller.set_player_number(idx);
+        controller.set_player_number(idx).unwrap();
         controller.set_mute(idx % 2 == 1);
         sleep(Duration::from_millis(5000));
         idx = (idx + 1) % 6;
@@ -11,6 +11,14 @@ fn main() {
     controller.on_accel_y_changed(&|val| println!("accel y: {val}"));
     controller.on_accel_z_changed(&|val| println!("accel z: {val}"));
 
+    controller.on_motion(Box::new(|motion| {
+        println!(
+            "gyro {:?} deg/s, accel {:?} g",
+            motion.gyro(),
+            motion.accel()
+        )
+    }));
+
     let handle = controller.run();
     handle.join().ok();
 }
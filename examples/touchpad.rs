@@ -0,0 +1,14 @@
+use dualsense_rs::{properties::touch::TouchEvent, DualSense};
+
+fn main() {
+    let mut controller = DualSense::default();
+
+    controller.on_touch_event(Box::new(|event| match event {
+        TouchEvent::Down(touch) => println!("finger {} down at {:?}", touch.id, touch.normalize()),
+        TouchEvent::Move(touch) => println!("finger {} moved to {:?}", touch.id, touch.normalize()),
+        TouchEvent::Up(touch) => println!("finger {} up", touch.id),
+    }));
+
+    let handle = controller.run();
+    handle.join().ok();
+}
@@ -12,28 +12,55 @@ fn main() {
     controller.on_right_force_enabled(&|val| println!("right force on/off {val}"));
 
     let _handle = controller.run();
-    controller.set_left_start_of_resistance(1);
-    controller.set_left_param2(100);
-    controller.set_left_param3(50);
-    controller.set_left_param4(150);
-    controller.set_left_param5(200);
-    controller.set_left_param6(200);
-    controller.set_left_param7(200);
 
     let effects = [
-        TriggerEffect::Mode1,
-        TriggerEffect::Mode2,
-        TriggerEffect::Mode1_4,
-        TriggerEffect::Mode2_4,
-        TriggerEffect::Mode1_20,
-        TriggerEffect::Mode1_4_20,
-        TriggerEffect::Mode2_4_20,
+        TriggerEffect::Feedback {
+            start: 2,
+            strength: 6,
+        },
+        TriggerEffect::Weapon {
+            start: 3,
+            end: 6,
+            strength: 8,
+        },
+        TriggerEffect::Vibration {
+            start: 0,
+            amplitude: 8,
+            frequency: 30,
+        },
+        TriggerEffect::SlopeFeedback {
+            start: 0,
+            end: 9,
+            start_strength: 1,
+            end_strength: 8,
+        },
+        TriggerEffect::Bow {
+            start: 1,
+            end: 6,
+            strength: 6,
+            snap_force: 8,
+        },
+        TriggerEffect::Galloping {
+            start: 0,
+            end: 9,
+            first_foot: 2,
+            second_foot: 4,
+            frequency: 3,
+        },
+        TriggerEffect::Machine {
+            start: 1,
+            end: 9,
+            amplitude_a: 3,
+            amplitude_b: 7,
+            frequency: 5,
+            period: 3,
+        },
     ];
     let mut idx = 0;
     loop {
         let i = idx % effects.len();
-        controller.set_left_trigger_effect(effects[i]);
-        println!("Set trigger to {i}");
+        controller.set_left_trigger_effect(effects[i]).unwrap();
+        println!("Set trigger to {:?}", effects[i]);
         sleep(Duration::from_millis(5000));
         idx += 1;
     }
@@ -2,6 +2,9 @@ use std::time::{Duration, Instant};
 
 use crate::properties::property::ComboProperty;
 
+/// Predicate deciding whether an input satisfies one of the combo's keys
+pub(crate) type ComboKey = Box<dyn Fn(&ComboProperty) -> bool + Send + Sync>;
+
 pub struct Combo {
     pub(crate) cbs: Vec<ComboRequirement>,
     pub(crate) on_success: Box<dyn Fn() + Send + Sync>,
@@ -12,7 +15,7 @@ pub struct Combo {
 
 impl Combo {
     pub(crate) fn new(
-        cbs: Vec<Box<dyn Fn(&ComboProperty) -> bool + Send + Sync>>,
+        cbs: Vec<ComboKey>,
         on_success: Box<dyn Fn() + Send + Sync>,
         duration: Duration,
     ) -> Self {
@@ -63,7 +66,7 @@ impl Combo {
 }
 
 pub(crate) struct ComboRequirement {
-    pub(crate) cb: Box<dyn Fn(&ComboProperty) -> bool + Send + Sync>,
+    pub(crate) cb: ComboKey,
     pub(crate) satisfied: bool,
 }
 
@@ -1,5 +1,5 @@
 pub mod stream;
 pub use stream::*;
-pub(crate) mod callback_helpers;
-pub mod properties;
 pub(crate) mod combo;
+pub mod properties;
+pub(crate) mod settings;
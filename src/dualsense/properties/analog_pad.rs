@@ -1,14 +1,24 @@
-use super::{property::ComboProperty, traits::Normalizable};
+use std::hash::{Hash, Hasher};
 
-const ANALOG_PAD_MIDDLE: u8 = u8::MAX / 2;
-/// About 90
-// const QUADRANT_ANGLE: u8 = (45_f64.cos() * PI / 180.0) as u8 * ANALOG_PAD_MIDDLE;
-const QUADRANT_ANGLE: u8 = 90;
+use super::{
+    calibration::StickCalibration, curve::Curve, dead_zone::DeadZone, direction::Direction,
+    property::ComboProperty, traits::Normalizable,
+};
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+/// Smallest distance from the center, before the calibration, dead zone and curves, at which the stick has a
+/// direction, so a resting stick without a dead zone does not point anywhere
+pub const MIN_DIRECTION_MAGNITUDE: f32 = 0.08;
+
+/// Position of an analog stick. Two pads are equal when their raw coordinates are equal, regardless of the
+/// calibration, dead zone and curves they were configured with
+#[derive(Clone, Copy, Debug)]
 pub struct AnalogPad {
     x: Stick,
     y: Stick,
+    calibration: StickCalibration,
+    dead_zone: DeadZone,
+    curve_x: Curve,
+    curve_y: Curve,
 }
 
 impl AnalogPad {
@@ -16,61 +26,123 @@ impl AnalogPad {
         AnalogPad {
             x: Stick(x),
             y: Stick(y),
+            calibration: StickCalibration::default(),
+            dead_zone: DeadZone::default(),
+            curve_x: Curve::default(),
+            curve_y: Curve::default(),
         }
     }
 
+    pub(crate) fn with_calibration(mut self, calibration: StickCalibration) -> Self {
+        self.calibration = calibration;
+        self
+    }
+
+    pub(crate) fn with_dead_zone(mut self, dead_zone: DeadZone) -> Self {
+        self.dead_zone = dead_zone;
+        self
+    }
+
+    pub(crate) fn with_curves(mut self, curve_x: Curve, curve_y: Curve) -> Self {
+        self.curve_x = curve_x;
+        self.curve_y = curve_y;
+        self
+    }
+
+    /// Raw coordinates as sent by the controller, 0 being left/up and 255 being right/down
+    pub fn raw(&self) -> (u8, u8) {
+        (self.x.0, self.y.0)
+    }
+
+    /// Calibration applied by `Self::normalize`
+    pub fn calibration(&self) -> StickCalibration {
+        self.calibration
+    }
+
+    /// Dead zone applied by `Self::normalize` and `Self::direction_quadrant`
+    pub fn dead_zone(&self) -> DeadZone {
+        self.dead_zone
+    }
+
+    /// Curves applied to the X and Y axes by `Self::normalize`
+    pub fn curves(&self) -> (Curve, Curve) {
+        (self.curve_x, self.curve_y)
+    }
+
     ///  \N/
     /// W X E
     ///  /S\
     pub fn direction_quadrant(&self) -> DirectionQuadrant {
+        match self.direction(4).map(|direction| direction.sector()) {
+            None => DirectionQuadrant::DeadZone,
+            Some(0) => DirectionQuadrant::North,
+            Some(1) => DirectionQuadrant::East,
+            Some(2) => DirectionQuadrant::South,
+            Some(_) => DirectionQuadrant::West,
+        }
+    }
+
+    /// Split the stick's range into `sectors` equal parts and find the one the stick points to. Returns `None`
+    /// while the stick is in its dead zone or closer to the center than `MIN_DIRECTION_MAGNITUDE`. Use a
+    /// `DirectionTracker` to avoid flickering at the sector boundaries
+    pub fn direction(&self, sectors: u16) -> Option<Direction> {
         if self.in_dead_zone() {
-            DirectionQuadrant::DeadZone
-        } else if Self::in_quadrant(self.x.0) {
-            if self.y.0 >= ANALOG_PAD_MIDDLE {
-                DirectionQuadrant::South
-            } else {
-                DirectionQuadrant::North
-            }
-        } else if Self::in_quadrant(self.y.0) {
-            if self.x.0 >= ANALOG_PAD_MIDDLE {
-                DirectionQuadrant::East
-            } else {
-                DirectionQuadrant::West
-            }
+            None
         } else {
-            unreachable!()
+            Some(Direction::from_angle(self.angle_degrees(), sectors))
         }
     }
 
-    /// Normalize values to the [-1, 1] interval
-    pub fn normalize(&self) -> (f32, f32) {
-        (self.x.normalize(), -self.y.normalize())
+    /// Angle of the stick in the [0, 360) interval, measured clockwise from north (stick pushed up), with the
+    /// dead zone and curves applied
+    pub fn angle_degrees(&self) -> f32 {
+        let (x, y) = self.normalize();
+        x.atan2(y).to_degrees().rem_euclid(360.0)
     }
 
-    fn in_quadrant(value: u8) -> bool {
-        (-180..=180).contains(&(value as i32 - 127))
+    /// Distance of the stick from its center in the [0, 1] interval, with the dead zone and curves applied
+    pub fn magnitude(&self) -> f32 {
+        let (x, y) = self.normalize();
+        x.hypot(y).min(1.0)
     }
 
-    fn in_dead_zone(&self) -> bool {
-        (ANALOG_PAD_MIDDLE - 10..=ANALOG_PAD_MIDDLE + 10).contains(&self.x.0)
-            && (ANALOG_PAD_MIDDLE - 10..=ANALOG_PAD_MIDDLE + 10).contains(&self.y.0)
+    /// Normalize values to the [-1, 1] interval, with the calibration, the dead zone and then the curves applied
+    pub fn normalize(&self) -> (f32, f32) {
+        let (x, y) = self.calibration.normalize(self.x.0, self.y.0);
+        let (x, y) = self.dead_zone.apply(x, y);
+        (self.curve_x.apply(x), self.curve_y.apply(y))
     }
 
-    pub(crate) fn to_left_combo(self) -> ComboProperty {
-        ComboProperty::LeftPad(self)
+    /// Normalize values to the [-1, 1] interval, ignoring the calibration, the dead zone and the curves
+    pub fn normalize_raw(&self) -> (f32, f32) {
+        (self.x.normalize(), -self.y.normalize())
     }
 
-    pub(crate) fn to_right_combo(self) -> ComboProperty {
-        ComboProperty::RightPad(self)
+    /// Whether the stick rests too close to the center to point in a direction
+    pub(crate) fn in_dead_zone(&self) -> bool {
+        let (x, y) = self.normalize_raw();
+        x.hypot(y) < MIN_DIRECTION_MAGNITUDE || self.normalize() == (0.0, 0.0)
     }
 }
 
 impl Default for AnalogPad {
     fn default() -> Self {
-        Self {
-            x: Stick(0),
-            y: Stick(0),
-        }
+        Self::new(0, 0)
+    }
+}
+
+impl PartialEq for AnalogPad {
+    fn eq(&self, other: &Self) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+}
+
+impl Eq for AnalogPad {}
+
+impl Hash for AnalogPad {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.x.hash(state);
+        self.y.hash(state);
     }
 }
 
@@ -100,3 +172,105 @@ pub enum DirectionQuadrant {
     West,
     DeadZone,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn normalize_raw_range() {
+        assert_eq!(AnalogPad::new(0, 0).normalize_raw(), (-1.0, 1.0));
+        assert_eq!(AnalogPad::new(255, 255).normalize_raw(), (1.0, -1.0));
+        let (x, y) = AnalogPad::new(128, 64).normalize_raw();
+        assert_close(x, 1.0 / 255.0);
+        assert_close(y, 1.0 - 128.0 / 255.0);
+    }
+
+    #[test]
+    fn default_normalize_is_raw() {
+        for (x, y) in [
+            (0, 0),
+            (255, 255),
+            (255, 0),
+            (0, 255),
+            (128, 128),
+            (127, 130),
+            (200, 40),
+        ] {
+            let pad = AnalogPad::new(x, y);
+            let (actual_x, actual_y) = pad.normalize();
+            let (expected_x, expected_y) = pad.normalize_raw();
+            assert_close(actual_x, expected_x);
+            assert_close(actual_y, expected_y);
+        }
+    }
+
+    #[test]
+    fn configured_dead_zone() {
+        let pad = AnalogPad::new(140, 128).with_dead_zone(DeadZone::none().inner(0.2));
+        assert_eq!(pad.normalize(), (0.0, 0.0));
+        assert_eq!(pad.direction(8), None);
+        assert!(AnalogPad::new(140, 128).direction(8).is_some());
+    }
+
+    #[test]
+    fn angle_degrees() {
+        assert_close(AnalogPad::new(128, 0).angle_degrees().round(), 0.0);
+        assert_close(AnalogPad::new(255, 128).angle_degrees().round(), 90.0);
+        assert_close(AnalogPad::new(128, 255).angle_degrees().round(), 180.0);
+        assert_close(AnalogPad::new(0, 128).angle_degrees().round(), 270.0);
+        assert_close(AnalogPad::new(255, 0).angle_degrees(), 45.0);
+    }
+
+    #[test]
+    fn magnitude() {
+        assert_close(
+            AnalogPad::new(128, 128).magnitude(),
+            (1.0_f32 / 255.0).hypot(1.0 / 255.0),
+        );
+        assert_close(AnalogPad::new(255, 128).magnitude(), 1.0);
+        assert_close(
+            AnalogPad::new(128, 64).magnitude(),
+            (1.0_f32 / 255.0).hypot(1.0 - 128.0 / 255.0),
+        );
+        // the corners are clamped to the unit circle
+        assert_close(AnalogPad::new(0, 0).magnitude(), 1.0);
+    }
+
+    #[test]
+    fn direction() {
+        assert_eq!(AnalogPad::new(128, 128).direction(8), None);
+        assert_eq!(AnalogPad::new(133, 122).direction(8), None);
+        assert_eq!(
+            AnalogPad::new(128, 0).direction(8).map(|d| d.sector()),
+            Some(0)
+        );
+        assert_eq!(
+            AnalogPad::new(255, 0).direction(8).map(|d| d.sector()),
+            Some(1)
+        );
+        assert_eq!(
+            AnalogPad::new(255, 128).direction(8).map(|d| d.sector()),
+            Some(2)
+        );
+        assert_eq!(
+            AnalogPad::new(0, 0).direction(8).map(|d| d.sector()),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn direction_quadrant() {
+        let quadrant = |x, y| AnalogPad::new(x, y).direction_quadrant();
+        assert_eq!(quadrant(128, 128), DirectionQuadrant::DeadZone);
+        assert_eq!(quadrant(128, 0), DirectionQuadrant::North);
+        assert_eq!(quadrant(255, 128), DirectionQuadrant::East);
+        assert_eq!(quadrant(128, 255), DirectionQuadrant::South);
+        assert_eq!(quadrant(0, 128), DirectionQuadrant::West);
+        assert_eq!(quadrant(200, 20), DirectionQuadrant::North);
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    f32::consts::PI,
+    sync::{Arc, Mutex},
+};
+
+use super::lightbar::{Color, Lightbar};
+
+/// How the color moves from one keyframe to the next
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts slow and speeds up
+    EaseIn,
+    /// Starts fast and slows down
+    EaseOut,
+    /// Slow at both ends, following a sine wave
+    EaseInOut,
+    /// Keeps the previous color until the keyframe is reached
+    Step,
+}
+
+impl Easing {
+    /// Map the progress between two keyframes, in the [0, 1] interval
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => (1.0 - (PI * t).cos()) / 2.0,
+            Easing::Step => {
+                if t >= 1.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Color reached at a point in time, `easing` being used for the transition from the previous keyframe
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+    pub seconds: f32,
+    pub color: Color,
+    pub easing: Easing,
+}
+
+/// Timeline of lightbar colors. Colors are computed only from the time elapsed since the animation started, so
+/// the same time always gives the same color
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LightbarAnimation {
+    keyframes: Vec<Keyframe>,
+    looping: bool,
+}
+
+impl LightbarAnimation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a keyframe `seconds` after the start, keyframes are kept sorted by time
+    pub fn keyframe(mut self, seconds: f32, color: Color, easing: Easing) -> Self {
+        let index = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.seconds <= seconds);
+        self.keyframes.insert(
+            index,
+            Keyframe {
+                seconds: seconds.max(0.0),
+                color,
+                easing,
+            },
+        );
+        self
+    }
+
+    /// Restart from the beginning after the last keyframe instead of stopping on its color
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Go from one color to another once
+    pub fn fade(from: Color, to: Color, seconds: f32) -> Self {
+        Self::new()
+            .keyframe(0.0, from, Easing::Linear)
+            .keyframe(seconds, to, Easing::Linear)
+    }
+
+    /// Light up quickly and fade out, repeating every `seconds`
+    pub fn pulse(color: Color, seconds: f32) -> Self {
+        Self::new()
+            .keyframe(0.0, Color::BLACK, Easing::Linear)
+            .keyframe(seconds * 0.15, color, Easing::EaseOut)
+            .keyframe(seconds, Color::BLACK, Easing::EaseIn)
+            .looping(true)
+    }
+
+    /// Slowly dim and brighten, repeating every `seconds`
+    pub fn breathe(color: Color, seconds: f32) -> Self {
+        let dim = color.scale(0.1);
+        Self::new()
+            .keyframe(0.0, dim, Easing::Linear)
+            .keyframe(seconds / 2.0, color, Easing::EaseInOut)
+            .keyframe(seconds, dim, Easing::EaseInOut)
+            .looping(true)
+    }
+
+    /// Go through every hue, repeating every `seconds`
+    pub fn rainbow(seconds: f32) -> Self {
+        (0..=6)
+            .fold(Self::new(), |animation, step| {
+                animation.keyframe(
+                    seconds * step as f32 / 6.0,
+                    Color::from_hsv(step as f32 * 60.0, 1.0, 1.0),
+                    Easing::Linear,
+                )
+            })
+            .looping(true)
+    }
+
+    /// Flash on and off `frequency` times a second
+    pub fn strobe(color: Color, frequency: f32) -> Self {
+        let period = 1.0 / frequency.max(f32::EPSILON);
+        Self::new()
+            .keyframe(0.0, color, Easing::Step)
+            .keyframe(period / 2.0, Color::BLACK, Easing::Step)
+            .keyframe(period, color, Easing::Step)
+            .looping(true)
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Time of the last keyframe, in seconds
+    pub fn duration(&self) -> f32 {
+        self.keyframes
+            .last()
+            .map_or(0.0, |keyframe| keyframe.seconds)
+    }
+
+    /// Whether a one-shot animation reached its last keyframe `seconds` after it started
+    pub fn is_finished(&self, seconds: f32) -> bool {
+        !self.looping && seconds >= self.duration()
+    }
+
+    /// Color `seconds` after the animation started, black without keyframes
+    pub fn color_at(&self, seconds: f32) -> Color {
+        let (first, last) = match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Color::BLACK,
+        };
+        let duration = self.duration();
+        let seconds = if self.looping && duration > 0.0 {
+            seconds.max(0.0) % duration
+        } else {
+            seconds
+        };
+        if seconds <= first.seconds {
+            return first.color;
+        }
+        if seconds >= last.seconds {
+            return last.color;
+        }
+
+        let next = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.seconds <= seconds);
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let progress = (seconds - from.seconds) / (to.seconds - from.seconds).max(f32::EPSILON);
+        from.color.lerp(to.color, to.easing.apply(progress))
+    }
+}
+
+/// Animation playing on the lightbar, see `DualSense::play_lightbar_animation`
+#[derive(Clone, Debug)]
+pub struct AnimationHandle {
+    id: u32,
+    lightbar: Arc<Mutex<Lightbar>>,
+}
+
+impl AnimationHandle {
+    pub(crate) fn new(id: u32, lightbar: Arc<Mutex<Lightbar>>) -> Self {
+        Self { id, lightbar }
+    }
+
+    /// Stop the animation and go back to the lightbar color, does nothing if another animation replaced it
+    pub fn stop(&self) {
+        self.lightbar.lock().unwrap().stop(Some(self.id));
+    }
+
+    /// Whether the animation is still playing: it was not stopped, replaced or, for one-shot animations, finished
+    pub fn is_playing(&self) -> bool {
+        self.lightbar.lock().unwrap().is_playing(self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors_at(animation: &LightbarAnimation, times: &[f32]) -> Vec<Color> {
+        times
+            .iter()
+            .map(|seconds| animation.color_at(*seconds))
+            .collect()
+    }
+
+    #[test]
+    fn breathe() {
+        let color = Color::new(200, 100, 0);
+        let dim = Color::new(20, 10, 0);
+        let half = Color::new(110, 55, 0);
+        assert_eq!(
+            colors_at(
+                &LightbarAnimation::breathe(color, 2.0),
+                &[0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0]
+            ),
+            [dim, half, color, half, dim, half, color]
+        );
+    }
+
+    #[test]
+    fn rainbow() {
+        assert_eq!(
+            colors_at(
+                &LightbarAnimation::rainbow(6.0),
+                &[0.0, 0.5, 1.0, 2.0, 3.0, 4.0, 5.0, 5.5, 6.0]
+            ),
+            [
+                Color::RED,
+                Color::new(255, 128, 0),
+                Color::YELLOW,
+                Color::GREEN,
+                Color::CYAN,
+                Color::BLUE,
+                Color::MAGENTA,
+                Color::new(255, 0, 128),
+                Color::RED
+            ]
+        );
+    }
+
+    #[test]
+    fn strobe_blinks() {
+        assert_eq!(
+            colors_at(
+                &LightbarAnimation::strobe(Color::RED, 2.0),
+                &[0.0, 0.1, 0.24, 0.25, 0.4, 0.5, 0.6, 0.75, 10.1]
+            ),
+            [
+                Color::RED,
+                Color::RED,
+                Color::RED,
+                Color::BLACK,
+                Color::BLACK,
+                Color::RED,
+                Color::RED,
+                Color::BLACK,
+                Color::RED
+            ]
+        );
+    }
+
+    #[test]
+    fn one_shot_stops_on_last_color() {
+        let fade = LightbarAnimation::fade(Color::BLACK, Color::WHITE, 1.0);
+        assert_eq!(
+            colors_at(&fade, &[-1.0, 0.5, 1.0, 5.0]),
+            [
+                Color::BLACK,
+                Color::new(128, 128, 128),
+                Color::WHITE,
+                Color::WHITE
+            ]
+        );
+        assert!(!fade.is_finished(0.5));
+        assert!(fade.is_finished(1.0));
+        assert_eq!(LightbarAnimation::new().color_at(1.0), Color::BLACK);
+    }
+}
@@ -0,0 +1,272 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::analog_pad::AnalogPad;
+
+/// Resting position and range of motion of an analog stick, in raw units. Worn sticks rarely rest exactly at the
+/// middle of the 0-255 range or reach both of its ends
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StickCalibration {
+    pub center_x: f32,
+    pub center_y: f32,
+    pub min_x: f32,
+    pub max_x: f32,
+    pub min_y: f32,
+    pub max_y: f32,
+}
+
+impl StickCalibration {
+    /// Normalize raw coordinates to the [-1, 1] interval, with the Y axis pointing up
+    pub fn normalize(&self, x: u8, y: u8) -> (f32, f32) {
+        (
+            Self::normalize_axis(x as f32, self.center_x, self.min_x, self.max_x),
+            -Self::normalize_axis(y as f32, self.center_y, self.min_y, self.max_y),
+        )
+    }
+
+    /// Move the center by an offset given in normalized units, in the same orientation as `Self::normalize`
+    pub fn recenter(&mut self, offset: (f32, f32)) {
+        self.center_x += Self::to_raw(offset.0, self.center_x, self.min_x, self.max_x);
+        self.center_y += Self::to_raw(-offset.1, self.center_y, self.min_y, self.max_y);
+    }
+
+    fn normalize_axis(value: f32, center: f32, min: f32, max: f32) -> f32 {
+        let range = if value >= center {
+            max - center
+        } else {
+            center - min
+        };
+        ((value - center) / range.max(f32::EPSILON)).clamp(-1.0, 1.0)
+    }
+
+    fn to_raw(offset: f32, center: f32, min: f32, max: f32) -> f32 {
+        if offset >= 0.0 {
+            offset * (max - center)
+        } else {
+            offset * (center - min)
+        }
+    }
+}
+
+impl Default for StickCalibration {
+    /// Stick resting in the middle and covering the whole 0-255 range
+    fn default() -> Self {
+        Self {
+            center_x: 127.5,
+            center_y: 127.5,
+            min_x: 0.0,
+            max_x: 255.0,
+            min_y: 0.0,
+            max_y: 255.0,
+        }
+    }
+}
+
+/// Collects stick samples to build a `StickCalibration`. Feed it samples while the stick is left alone, then
+/// while the stick is rotated along its edge a few times
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StickCalibrator {
+    rest_sum: (f64, f64),
+    rest_samples: u32,
+    min: Option<(u8, u8)>,
+    max: Option<(u8, u8)>,
+}
+
+impl StickCalibrator {
+    /// Add a sample taken while the stick is not touched
+    pub fn sample_rest(&mut self, pad: &AnalogPad) {
+        let (x, y) = pad.raw();
+        self.rest_sum.0 += x as f64;
+        self.rest_sum.1 += y as f64;
+        self.rest_samples += 1;
+    }
+
+    /// Add a sample taken while the stick is rotated along its edge
+    pub fn sample_range(&mut self, pad: &AnalogPad) {
+        let (x, y) = pad.raw();
+        self.min = Some(match self.min {
+            Some((min_x, min_y)) => (min_x.min(x), min_y.min(y)),
+            None => (x, y),
+        });
+        self.max = Some(match self.max {
+            Some((max_x, max_y)) => (max_x.max(x), max_y.max(y)),
+            None => (x, y),
+        });
+    }
+
+    /// Build the calibration, values that were not sampled are taken from `StickCalibration::default`
+    pub fn finish(&self) -> StickCalibration {
+        let mut calibration = StickCalibration::default();
+        if self.rest_samples > 0 {
+            calibration.center_x = (self.rest_sum.0 / self.rest_samples as f64) as f32;
+            calibration.center_y = (self.rest_sum.1 / self.rest_samples as f64) as f32;
+        }
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            calibration.min_x = (min.0 as f32).min(calibration.center_x);
+            calibration.min_y = (min.1 as f32).min(calibration.center_y);
+            calibration.max_x = (max.0 as f32).max(calibration.center_x);
+            calibration.max_y = (max.1 as f32).max(calibration.center_y);
+        }
+        calibration
+    }
+}
+
+/// Tracks where the stick rests over time. Samples close to the center are considered resting and are averaged;
+/// once the average moves away from the center by more than the threshold the stick is drifting
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DriftMonitor {
+    rest_radius: f32,
+    threshold: f32,
+    smoothing: f32,
+    offset: (f32, f32),
+}
+
+impl DriftMonitor {
+    /// - `rest_radius`: samples further than this from the center, in normalized units, are ignored
+    /// - `threshold`: distance of the average resting position from the center over which the stick is drifting
+    /// - `smoothing`: weight of each new sample in the average, in the (0, 1] interval
+    pub fn new(rest_radius: f32, threshold: f32, smoothing: f32) -> Self {
+        Self {
+            rest_radius,
+            threshold,
+            smoothing: smoothing.clamp(f32::EPSILON, 1.0),
+            offset: (0.0, 0.0),
+        }
+    }
+
+    /// Feed calibrated coordinates, as given by `StickCalibration::normalize`
+    pub fn update(&mut self, x: f32, y: f32) {
+        if x.hypot(y) > self.rest_radius {
+            return;
+        }
+        self.offset.0 += (x - self.offset.0) * self.smoothing;
+        self.offset.1 += (y - self.offset.1) * self.smoothing;
+    }
+
+    /// Average resting position, in normalized units
+    pub fn offset(&self) -> (f32, f32) {
+        self.offset
+    }
+
+    pub fn is_drifting(&self) -> bool {
+        self.offset.0.hypot(self.offset.1) > self.threshold
+    }
+
+    pub fn reset(&mut self) {
+        self.offset = (0.0, 0.0);
+    }
+}
+
+impl Default for DriftMonitor {
+    /// Samples within 15% of the center are resting, drifting once the average moves over 4% away
+    fn default() -> Self {
+        Self::new(0.15, 0.04, 0.01)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn default_covers_the_whole_range() {
+        let calibration = StickCalibration::default();
+        assert_eq!(calibration.normalize(0, 0), (-1.0, 1.0));
+        assert_eq!(calibration.normalize(255, 255), (1.0, -1.0));
+    }
+
+    #[test]
+    fn calibrator_center_and_range() {
+        let mut calibrator = StickCalibrator::default();
+        for (x, y) in [(130, 120), (132, 122), (131, 121)] {
+            calibrator.sample_rest(&AnalogPad::new(x, y));
+        }
+        for (x, y) in [(131, 10), (240, 121), (131, 250), (5, 121), (200, 200)] {
+            calibrator.sample_range(&AnalogPad::new(x, y));
+        }
+        let calibration = calibrator.finish();
+        assert_close(calibration.center_x, 131.0);
+        assert_close(calibration.center_y, 121.0);
+        assert_eq!((calibration.min_x, calibration.max_x), (5.0, 240.0));
+        assert_eq!((calibration.min_y, calibration.max_y), (10.0, 250.0));
+
+        assert_eq!(calibration.normalize(131, 121), (0.0, -0.0));
+        assert_eq!(calibration.normalize(240, 10), (1.0, 1.0));
+        assert_eq!(calibration.normalize(5, 250), (-1.0, -1.0));
+        let (x, _) = calibration.normalize(68, 121);
+        assert_close(x, -0.5);
+    }
+
+    #[test]
+    fn calibrator_without_samples() {
+        assert_eq!(
+            StickCalibrator::default().finish(),
+            StickCalibration::default()
+        );
+        // a range that does not reach the center is stretched to it
+        let mut calibrator = StickCalibrator::default();
+        calibrator.sample_range(&AnalogPad::new(200, 200));
+        let calibration = calibrator.finish();
+        assert_eq!((calibration.min_x, calibration.max_x), (127.5, 200.0));
+    }
+
+    #[test]
+    fn recenter() {
+        let mut calibration = StickCalibration::default();
+        calibration.recenter((0.2, -0.2));
+        assert_close(calibration.center_x, 127.5 + 0.2 * 127.5);
+        assert_close(calibration.center_y, 127.5 + 0.2 * 127.5);
+    }
+
+    #[test]
+    fn drift_monitor_flags_a_moving_rest() {
+        let mut monitor = DriftMonitor::new(0.15, 0.04, 0.1);
+        for _ in 0..100 {
+            monitor.update(0.01, -0.01);
+        }
+        assert!(!monitor.is_drifting());
+        for _ in 0..100 {
+            monitor.update(0.08, 0.0);
+        }
+        assert!(monitor.is_drifting());
+        let (x, y) = monitor.offset();
+        assert_close(x, 0.08);
+        assert!(y.abs() < 1e-4);
+        monitor.reset();
+        assert!(!monitor.is_drifting());
+    }
+
+    #[test]
+    fn drift_monitor_ignores_movements() {
+        let mut monitor = DriftMonitor::default();
+        for _ in 0..1000 {
+            monitor.update(0.9, 0.0);
+            monitor.update(0.0, -0.5);
+        }
+        assert_eq!(monitor.offset(), (0.0, 0.0));
+        assert!(!monitor.is_drifting());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let calibration = StickCalibration {
+            center_x: 130.25,
+            center_y: 125.5,
+            min_x: 3.0,
+            max_x: 250.0,
+            min_y: 8.0,
+            max_y: 247.0,
+        };
+        let json = serde_json::to_string(&calibration).unwrap();
+        assert_eq!(
+            serde_json::from_str::<StickCalibration>(&json).unwrap(),
+            calibration
+        );
+    }
+}
@@ -1,11 +1,11 @@
 use std::time::Duration;
 
-use crate::combo::Combo;
+use crate::combo::{Combo, ComboKey};
 
 use super::{property::ComboProperty, traits::ComboAble};
 
 pub struct SimultaneousCombo {
-    pub(crate) cbs: Vec<Box<dyn Fn(&ComboProperty) -> bool + Send + Sync>>,
+    pub(crate) cbs: Vec<ComboKey>,
     pub(crate) on_success: Box<dyn Fn() + Send + Sync>,
     pub(crate) duration: Duration,
 }
@@ -20,20 +20,17 @@ impl ComboAble for SimultaneousCombo {
         self.duration = duration;
         self
     }
-    
 
     fn done(mut self, cb: Box<dyn Fn() + Send + Sync>) -> Self {
         self.on_success = cb;
         self
     }
-    
-    
 
     fn build(self) -> Combo {
         Combo::new(self.cbs, self.on_success, self.duration)
     }
-    
-    fn cooldown(self, duration: Duration) -> Self {
+
+    fn cooldown(self, _duration: Duration) -> Self {
         todo!()
     }
 }
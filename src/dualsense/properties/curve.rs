@@ -0,0 +1,253 @@
+use std::{error::Error, fmt};
+
+/// Maximum number of points a lookup table curve can hold
+pub const MAX_LOOKUP_POINTS: usize = 16;
+
+/// Response curve mapping a normalized input magnitude to an output magnitude. Negative inputs are mirrored, so
+/// the same curve works for sticks in the [-1, 1] interval and for triggers in the [0, 1] interval
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Curve {
+    #[default]
+    Linear,
+    /// `(e^(k * x) - 1) / (e^k - 1)`, values of `k` over 0 make small movements finer
+    Exponential(f32),
+    /// `x^p`, values of `p` over 1 make small movements finer
+    Power(f32),
+    /// Slow near the rest position and near the end of the range, fast in the middle. The parameter is the
+    /// steepness, 1 being linear
+    SCurve(f32),
+    /// Piecewise linear interpolation between points
+    Lookup(LookupTable),
+}
+
+impl Curve {
+    /// Build a lookup table curve from `(input, output)` points in the [0, 1] interval. The points are sorted
+    /// by input, only the first `MAX_LOOKUP_POINTS` are kept. The curve starts from `(0, 0)` and keeps the
+    /// output of the last point after it. Fails without points or with a point outside of the [0, 1] interval
+    pub fn lookup(points: &[(f32, f32)]) -> Result<Self, CurveError> {
+        if points.is_empty() {
+            return Err(CurveError::Empty);
+        }
+        if let Some(point) = points
+            .iter()
+            .find(|(x, y)| !(0.0..=1.0).contains(x) || !(0.0..=1.0).contains(y))
+        {
+            return Err(CurveError::OutOfRange(*point));
+        }
+        let mut table = LookupTable {
+            points: [(0.0, 0.0); MAX_LOOKUP_POINTS],
+            len: points.len().min(MAX_LOOKUP_POINTS),
+        };
+        table.points[..table.len].copy_from_slice(&points[..table.len]);
+        table.points[..table.len].sort_by(|l, r| l.0.total_cmp(&r.0));
+        Ok(Self::Lookup(table))
+    }
+
+    /// Map a value from the [-1, 1] interval through the curve
+    pub fn apply(&self, value: f32) -> f32 {
+        let magnitude = value.abs().min(1.0);
+        let out = match self {
+            Curve::Linear => magnitude,
+            Curve::Exponential(k) => {
+                if k.abs() < f32::EPSILON {
+                    magnitude
+                } else {
+                    (k * magnitude).exp_m1() / k.exp_m1()
+                }
+            }
+            Curve::Power(p) => magnitude.powf(p.max(f32::EPSILON)),
+            Curve::SCurve(steepness) => {
+                let steepness = steepness.max(f32::EPSILON);
+                let rising = magnitude.powf(steepness);
+                let falling = (1.0 - magnitude).powf(steepness);
+                rising / (rising + falling)
+            }
+            Curve::Lookup(table) => table.interpolate(magnitude),
+        };
+        out.clamp(0.0, 1.0).copysign(value)
+    }
+}
+
+/// Fixed size list of points used by `Curve::Lookup`, build it with `Curve::lookup`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LookupTable {
+    points: [(f32, f32); MAX_LOOKUP_POINTS],
+    len: usize,
+}
+
+impl LookupTable {
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points[..self.len]
+    }
+
+    fn interpolate(&self, x: f32) -> f32 {
+        let mut previous = (0.0, 0.0);
+        for &(px, py) in self.points() {
+            if x <= px {
+                let range = px - previous.0;
+                if range <= f32::EPSILON {
+                    return py;
+                }
+                return previous.1 + (x - previous.0) / range * (py - previous.1);
+            }
+            previous = (px, py);
+        }
+        previous.1
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurveError {
+    /// A lookup table needs at least one point
+    Empty,
+    /// A lookup table point is outside of the [0, 1] interval
+    OutOfRange((f32, f32)),
+}
+
+impl fmt::Display for CurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurveError::Empty => write!(f, "lookup table curve without points"),
+            CurveError::OutOfRange((x, y)) => {
+                write!(
+                    f,
+                    "lookup table point ({x}, {y}) outside of the [0, 1] interval"
+                )
+            }
+        }
+    }
+}
+
+impl Error for CurveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-5, "{actual} != {expected}");
+    }
+
+    fn assert_curve(curve: Curve, points: &[(f32, f32)]) {
+        for &(input, output) in points {
+            assert_close(curve.apply(input), output);
+        }
+    }
+
+    #[test]
+    fn linear() {
+        assert_curve(
+            Curve::Linear,
+            &[
+                (0.0, 0.0),
+                (0.5, 0.5),
+                (-0.25, -0.25),
+                (1.5, 1.0),
+                (-3.0, -1.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn exponential() {
+        assert_curve(
+            Curve::Exponential(2.0),
+            &[
+                (0.0, 0.0),
+                (0.5, 0.268_941),
+                (-0.5, -0.268_941),
+                (1.0, 1.0),
+                (2.0, 1.0),
+            ],
+        );
+        assert_curve(Curve::Exponential(-2.0), &[(0.5, 0.731_059), (-1.5, -1.0)]);
+        // no curvature is linear instead of dividing by 0
+        assert_curve(Curve::Exponential(0.0), &[(0.3, 0.3), (-0.7, -0.7)]);
+        assert_curve(Curve::Exponential(1e-9), &[(0.3, 0.3), (-0.7, -0.7)]);
+    }
+
+    #[test]
+    fn power() {
+        assert_curve(
+            Curve::Power(2.0),
+            &[(0.5, 0.25), (-0.5, -0.25), (1.0, 1.0), (-1.2, -1.0)],
+        );
+        assert_curve(Curve::Power(0.5), &[(0.25, 0.5), (-0.25, -0.5)]);
+    }
+
+    #[test]
+    fn s_curve() {
+        assert_curve(
+            Curve::SCurve(2.0),
+            &[
+                (0.0, 0.0),
+                (0.25, 0.1),
+                (0.5, 0.5),
+                (0.75, 0.9),
+                (-0.75, -0.9),
+                (1.0, 1.0),
+                (4.0, 1.0),
+            ],
+        );
+        assert_curve(Curve::SCurve(1.0), &[(0.3, 0.3), (-0.6, -0.6)]);
+    }
+
+    #[test]
+    fn lookup() {
+        let curve = Curve::lookup(&[(0.5, 0.2), (1.0, 1.0)]).unwrap();
+        assert_curve(
+            curve,
+            &[
+                (0.0, 0.0),
+                (0.25, 0.1),
+                (0.5, 0.2),
+                (0.75, 0.6),
+                (-0.75, -0.6),
+                (1.0, 1.0),
+                (1.5, 1.0),
+            ],
+        );
+
+        let short = Curve::lookup(&[(0.4, 0.8), (0.8, 0.9)]).unwrap();
+        assert_curve(short, &[(0.2, 0.4), (0.8, 0.9), (1.0, 0.9), (-1.0, -0.9)]);
+    }
+
+    #[test]
+    fn lookup_sorts_points() {
+        assert_eq!(
+            Curve::lookup(&[(1.0, 1.0), (0.2, 0.5), (0.5, 0.6)]),
+            Curve::lookup(&[(0.2, 0.5), (0.5, 0.6), (1.0, 1.0)])
+        );
+        let Ok(Curve::Lookup(table)) = Curve::lookup(&[(1.0, 1.0), (0.5, 0.2)]) else {
+            unreachable!()
+        };
+        assert_eq!(table.points(), [(0.5, 0.2), (1.0, 1.0)]);
+    }
+
+    #[test]
+    fn lookup_keeps_the_first_points() {
+        let points = (1..=20)
+            .map(|i| (i as f32 / 20.0, i as f32 / 20.0))
+            .collect::<Vec<_>>();
+        let Ok(Curve::Lookup(table)) = Curve::lookup(&points) else {
+            unreachable!()
+        };
+        assert_eq!(table.points(), &points[..MAX_LOOKUP_POINTS]);
+        // the output of the last kept point is held
+        assert_curve(Curve::Lookup(table), &[(0.5, 0.5), (0.8, 0.8), (1.0, 0.8)]);
+    }
+
+    #[test]
+    fn lookup_errors() {
+        assert_eq!(Curve::lookup(&[]), Err(CurveError::Empty));
+        assert_eq!(
+            Curve::lookup(&[(0.5, 0.5), (1.5, 1.0)]),
+            Err(CurveError::OutOfRange((1.5, 1.0)))
+        );
+        assert_eq!(
+            Curve::lookup(&[(0.5, -0.1)]),
+            Err(CurveError::OutOfRange((0.5, -0.1)))
+        );
+        assert!(Curve::lookup(&[(f32::NAN, 0.5)]).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+/// Shape of the area around the center of a stick in which input is ignored
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum DeadZoneShape {
+    /// Each axis is ignored independently while it is inside the inner radius, snaps input to the axes
+    Axial,
+    /// Input is ignored while inside the inner circle. Outside of it the magnitude is only divided by the outer
+    /// radius, so it jumps from 0 to about the inner radius when leaving the circle
+    Radial,
+    /// Input is ignored while inside the inner circle, the magnitude is rescaled so it starts from 0
+    #[default]
+    ScaledRadial,
+    /// Radial check for the center followed by a scaled axial pass, keeps diagonals reachable while
+    /// removing drift along a single axis
+    Hybrid,
+}
+
+/// Dead zone configuration for an analog stick. All radii are given in normalized units, 0 being the
+/// center and 1 being the edge of the stick's range
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeadZone {
+    pub(crate) inner: f32,
+    pub(crate) outer: f32,
+    pub(crate) shape: DeadZoneShape,
+    pub(crate) anti_dead_zone: f32,
+}
+
+impl DeadZone {
+    /// Dead zone that does not alter the input
+    pub fn none() -> Self {
+        Self {
+            inner: 0.0,
+            outer: 1.0,
+            // the radial shapes would pull the corners onto the unit circle
+            shape: DeadZoneShape::Axial,
+            anti_dead_zone: 0.0,
+        }
+    }
+
+    /// Radius under which the input is ignored
+    pub fn inner(mut self, radius: f32) -> Self {
+        self.inner = radius.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Radius over which the input is considered fully tilted
+    pub fn outer(mut self, radius: f32) -> Self {
+        self.outer = radius.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn shape(mut self, shape: DeadZoneShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Smallest magnitude reported once the stick leaves the dead zone, used to cancel out the dead zone
+    /// a game already applies on its own
+    pub fn anti_dead_zone(mut self, radius: f32) -> Self {
+        self.anti_dead_zone = radius.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Apply the dead zone to normalized coordinates in the [-1, 1] interval
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let magnitude = x.hypot(y);
+        // the radial shapes divide by the magnitude
+        if magnitude == 0.0 {
+            return (0.0, 0.0);
+        }
+        let (x, y) = match self.shape {
+            DeadZoneShape::Axial => (self.scale_axis(x), self.scale_axis(y)),
+            DeadZoneShape::Radial => {
+                if magnitude < self.inner {
+                    (0.0, 0.0)
+                } else {
+                    let saturation = self.outer.max(f32::EPSILON);
+                    let factor = (magnitude / saturation).min(1.0) / magnitude;
+                    (x * factor, y * factor)
+                }
+            }
+            DeadZoneShape::ScaledRadial => {
+                if magnitude < self.inner {
+                    (0.0, 0.0)
+                } else {
+                    let factor = self.scale(magnitude) / magnitude;
+                    (x * factor, y * factor)
+                }
+            }
+            DeadZoneShape::Hybrid => {
+                if magnitude < self.inner {
+                    (0.0, 0.0)
+                } else {
+                    (self.scale_axis(x), self.scale_axis(y))
+                }
+            }
+        };
+        self.apply_anti_dead_zone(x, y)
+    }
+
+    fn apply_anti_dead_zone(&self, x: f32, y: f32) -> (f32, f32) {
+        let magnitude = x.hypot(y);
+        if self.anti_dead_zone == 0.0 || magnitude == 0.0 {
+            return (x, y);
+        }
+        let boosted = self.anti_dead_zone + magnitude.min(1.0) * (1.0 - self.anti_dead_zone);
+        let factor = boosted / magnitude;
+        (x * factor, y * factor)
+    }
+
+    fn scale_axis(&self, value: f32) -> f32 {
+        self.scale(value.abs()).copysign(value)
+    }
+
+    /// Map a magnitude from the [inner, outer] interval to [0, 1]
+    fn scale(&self, magnitude: f32) -> f32 {
+        if magnitude < self.inner {
+            return 0.0;
+        }
+        let range = (self.outer - self.inner).max(f32::EPSILON);
+        ((magnitude - self.inner) / range).min(1.0)
+    }
+}
+
+impl Default for DeadZone {
+    /// No dead zone, see `Self::none`
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHAPES: [DeadZoneShape; 4] = [
+        DeadZoneShape::Axial,
+        DeadZoneShape::Radial,
+        DeadZoneShape::ScaledRadial,
+        DeadZoneShape::Hybrid,
+    ];
+
+    #[test]
+    fn center_without_dead_zone() {
+        for shape in SHAPES {
+            let dead_zone = DeadZone::none().shape(shape);
+            assert_eq!(dead_zone.apply(0.0, 0.0), (0.0, 0.0), "{shape:?}");
+            assert_eq!(
+                dead_zone.anti_dead_zone(0.2).apply(0.0, 0.0),
+                (0.0, 0.0),
+                "{shape:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn none_keeps_the_input() {
+        for (x, y) in [(0.3, -0.2), (1.0, 1.0), (-1.0, 0.5), (0.01, 0.0)] {
+            assert_eq!(DeadZone::none().apply(x, y), (x, y));
+            assert_eq!(DeadZone::default().apply(x, y), (x, y));
+        }
+    }
+
+    #[test]
+    fn radial_divides_by_outer() {
+        let dead_zone = DeadZone::none()
+            .inner(0.2)
+            .outer(0.8)
+            .shape(DeadZoneShape::Radial);
+        assert_eq!(dead_zone.apply(0.4, 0.0), (0.5, 0.0));
+        let (x, y) = dead_zone.apply(0.3, 0.4);
+        assert!(
+            (x - 0.375).abs() < 1e-6 && (y - 0.5).abs() < 1e-6,
+            "{x} {y}"
+        );
+        // not shifted to start from 0, unlike the scaled shape
+        let (x, _) = dead_zone.apply(0.2, 0.0);
+        assert!((x - 0.25).abs() < 1e-6, "{x}");
+        let (x, _) = dead_zone.shape(DeadZoneShape::ScaledRadial).apply(0.2, 0.0);
+        assert_eq!(x, 0.0);
+        assert_eq!(dead_zone.apply(0.9, 0.0), (1.0, 0.0));
+    }
+
+    #[test]
+    fn inside_and_outside() {
+        for shape in SHAPES {
+            let dead_zone = DeadZone::default().inner(0.2).shape(shape);
+            assert_eq!(dead_zone.apply(0.1, -0.1), (0.0, 0.0), "{shape:?}");
+            assert_eq!(dead_zone.apply(1.0, 0.0), (1.0, 0.0), "{shape:?}");
+        }
+    }
+}
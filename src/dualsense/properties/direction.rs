@@ -0,0 +1,178 @@
+use super::{analog_pad::AnalogPad, dpad::DPad};
+
+/// One of the equally sized sectors a stick's range is split into. Sectors are counted clockwise, sector 0 being
+/// centered on north (stick pushed up)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Direction {
+    sector: u16,
+    sectors: u16,
+}
+
+impl Direction {
+    /// Find the sector an angle belongs to, the angle is measured clockwise from north
+    pub(crate) fn from_angle(angle_degrees: f32, sectors: u16) -> Self {
+        let sectors = sectors.max(1);
+        let width = 360.0 / sectors as f32;
+        let sector = ((angle_degrees + width / 2.0) / width).floor() as i64;
+        Self {
+            sector: sector.rem_euclid(sectors as i64) as u16,
+            sectors,
+        }
+    }
+
+    pub fn sector(&self) -> u16 {
+        self.sector
+    }
+
+    /// Total number of sectors the stick's range was split into
+    pub fn sectors(&self) -> u16 {
+        self.sectors
+    }
+
+    /// Angle in the middle of the sector, measured clockwise from north
+    pub fn center_degrees(&self) -> f32 {
+        self.sector as f32 * 360.0 / self.sectors as f32
+    }
+
+    /// The closest of the 8 dpad directions
+    pub fn to_dpad(&self) -> DPad {
+        DPad::from(((self.center_degrees() / 45.0).round() as u8) % 8)
+    }
+
+    fn contains(&self, angle_degrees: f32, margin_degrees: f32) -> bool {
+        let half_width = 180.0 / self.sectors as f32;
+        angle_distance(angle_degrees, self.center_degrees()) <= half_width + margin_degrees
+    }
+}
+
+/// Classifies stick positions into directions and keeps the previous direction until the stick moves past the
+/// sector's boundary by more than the hysteresis angle, to avoid flickering between neighbouring directions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DirectionTracker {
+    sectors: u16,
+    hysteresis_degrees: f32,
+    current: Option<Direction>,
+}
+
+impl DirectionTracker {
+    /// Use 4 or 8 sectors to match the dpad, any other count is also supported
+    pub fn new(sectors: u16, hysteresis_degrees: f32) -> Self {
+        Self {
+            sectors: sectors.max(1),
+            hysteresis_degrees: hysteresis_degrees.max(0.0),
+            current: None,
+        }
+    }
+
+    /// Feed a new stick position, returns `None` while the stick is in its dead zone
+    pub fn update(&mut self, pad: &AnalogPad) -> Option<Direction> {
+        if pad.in_dead_zone() {
+            self.current = None;
+            return None;
+        }
+        let angle = pad.angle_degrees();
+        match self.current {
+            Some(current) if current.contains(angle, self.hysteresis_degrees) => {}
+            _ => self.current = Some(Direction::from_angle(angle, self.sectors)),
+        }
+        self.current
+    }
+
+    pub fn current(&self) -> Option<Direction> {
+        self.current
+    }
+}
+
+impl Default for DirectionTracker {
+    /// 8 directions with a 10 degree hysteresis
+    fn default() -> Self {
+        Self::new(8, 10.0)
+    }
+}
+
+/// Smallest absolute difference between two angles, in degrees
+fn angle_distance(left: f32, right: f32) -> f32 {
+    let difference = (left - right).rem_euclid(360.0);
+    difference.min(360.0 - difference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stick pushed all the way at about `angle_degrees`
+    fn pad(angle_degrees: f32) -> AnalogPad {
+        let radians = angle_degrees.to_radians();
+        let x = 127.5 + 127.5 * radians.sin();
+        let y = 127.5 - 127.5 * radians.cos();
+        let pad = AnalogPad::new(x.round() as u8, y.round() as u8);
+        assert!(angle_distance(pad.angle_degrees(), angle_degrees) < 1.0);
+        pad
+    }
+
+    fn sectors(tracker: &mut DirectionTracker, angles: &[f32]) -> Vec<Option<u16>> {
+        angles
+            .iter()
+            .map(|&angle| {
+                tracker
+                    .update(&pad(angle))
+                    .map(|direction| direction.sector())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn from_angle() {
+        assert_eq!(Direction::from_angle(0.0, 8).sector(), 0);
+        assert_eq!(Direction::from_angle(22.0, 8).sector(), 0);
+        assert_eq!(Direction::from_angle(23.0, 8).sector(), 1);
+        assert_eq!(Direction::from_angle(350.0, 8).sector(), 0);
+        assert_eq!(Direction::from_angle(270.0, 4).sector(), 3);
+        assert_eq!(Direction::from_angle(135.0, 8).to_dpad(), DPad::DownRight);
+    }
+
+    #[test]
+    fn wobbling_across_a_boundary_keeps_the_direction() {
+        let mut tracker = DirectionTracker::default();
+        // the boundary between north and north-east is at 22.5 degrees
+        let wobble = [10.0, 20.0, 25.0, 19.0, 28.0, 21.0, 30.0, 24.0];
+        assert_eq!(sectors(&mut tracker, &wobble), vec![Some(0); wobble.len()]);
+        // the same wobble seen from the other side stays north-east
+        let mut tracker = DirectionTracker::default();
+        let wobble = [40.0, 25.0, 20.0, 24.0, 15.0, 21.0];
+        assert_eq!(sectors(&mut tracker, &wobble), vec![Some(1); wobble.len()]);
+    }
+
+    #[test]
+    fn switches_once_past_the_margin() {
+        let mut tracker = DirectionTracker::default();
+        assert_eq!(
+            sectors(&mut tracker, &[0.0, 30.0, 36.0, 30.0, 14.0, 10.0, 20.0]),
+            vec![
+                Some(0),
+                Some(0),
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(0),
+                Some(0)
+            ]
+        );
+        // without hysteresis the boundary is followed exactly
+        let mut tracker = DirectionTracker::new(8, 0.0);
+        assert_eq!(
+            sectors(&mut tracker, &[20.0, 25.0, 20.0]),
+            vec![Some(0), Some(1), Some(0)]
+        );
+    }
+
+    #[test]
+    fn resets_in_the_dead_zone() {
+        let mut tracker = DirectionTracker::default();
+        assert_eq!(sectors(&mut tracker, &[10.0, 30.0]), vec![Some(0), Some(0)]);
+        assert_eq!(tracker.update(&AnalogPad::new(128, 128)), None);
+        assert_eq!(tracker.current(), None);
+        // coming back from the center uses the plain sector
+        assert_eq!(tracker.update(&pad(30.0)).map(|d| d.sector()), Some(1));
+    }
+}
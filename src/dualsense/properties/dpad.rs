@@ -1,5 +1,5 @@
 /// Directional pad values
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
 pub enum DPad {
     Up = 0,
     UpRight = 1,
@@ -9,6 +9,7 @@ pub enum DPad {
     DownLeft = 5,
     Left = 6,
     UpLeft = 7,
+    #[default]
     None = 8,
 }
 
@@ -27,9 +28,3 @@ impl From<u8> for DPad {
         }
     }
 }
-
-impl Default for DPad {
-    fn default() -> Self {
-        Self::None
-    }
-}
@@ -0,0 +1,362 @@
+use std::ops::Mul;
+
+use super::motion::MotionSample;
+
+/// Reports further apart than this are treated as a gap in the data and are not integrated
+const MAX_STEP_SECONDS: f32 = 0.1;
+
+/// Rotation stored as a unit quaternion
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quaternion {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Quaternion {
+    pub const IDENTITY: Self = Self {
+        w: 1.0,
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    pub fn new(w: f32, x: f32, y: f32, z: f32) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// Smallest rotation taking the `from` direction onto the `to` direction
+    pub fn from_to(from: [f32; 3], to: [f32; 3]) -> Self {
+        let (from, to) = (normalize(from), normalize(to));
+        let axis = cross(from, to);
+        let dot = from[0] * to[0] + from[1] * to[1] + from[2] * to[2];
+        if dot < -0.999_999 {
+            // opposite directions, rotate half a turn around any perpendicular axis
+            let perpendicular = if from[0].abs() < 0.9 {
+                cross(from, [1.0, 0.0, 0.0])
+            } else {
+                cross(from, [0.0, 1.0, 0.0])
+            };
+            let [x, y, z] = normalize(perpendicular);
+            return Self::new(0.0, x, y, z);
+        }
+        Self::new(1.0 + dot, axis[0], axis[1], axis[2]).normalize()
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn normalize(&self) -> Self {
+        let norm = (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        if norm == 0.0 {
+            return Self::IDENTITY;
+        }
+        Self::new(self.w / norm, self.x / norm, self.y / norm, self.z / norm)
+    }
+
+    /// Apply the rotation to a vector
+    pub fn rotate(&self, v: [f32; 3]) -> [f32; 3] {
+        let rotated = *self * Self::new(0.0, v[0], v[1], v[2]) * self.conjugate();
+        [rotated.x, rotated.y, rotated.z]
+    }
+
+    /// Yaw (around Y, the up axis), pitch (around X) and roll (around Z) in degrees, applied in this order
+    pub fn yaw_pitch_roll(&self) -> (f32, f32, f32) {
+        let Self { w, x, y, z } = *self;
+        let pitch = (2.0 * (w * x - y * z)).clamp(-1.0, 1.0).asin();
+        let yaw = (2.0 * (x * z + w * y)).atan2(1.0 - 2.0 * (x * x + y * y));
+        let roll = (2.0 * (x * y + w * z)).atan2(1.0 - 2.0 * (x * x + z * z));
+        (yaw.to_degrees(), pitch.to_degrees(), roll.to_degrees())
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Self;
+
+    fn mul(self, r: Self) -> Self {
+        Self::new(
+            self.w * r.w - self.x * r.x - self.y * r.y - self.z * r.z,
+            self.w * r.x + self.x * r.w + self.y * r.z - self.z * r.y,
+            self.w * r.y - self.x * r.z + self.y * r.w + self.z * r.x,
+            self.w * r.z + self.x * r.y - self.y * r.x + self.z * r.w,
+        )
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Mahony complementary filter estimating the controller's orientation from the gyroscope and the
+/// accelerometer. The gyroscope is integrated and its drift is corrected by pulling the estimated up direction
+/// towards the one measured by the accelerometer. The reference frame is the controller lying flat, Y pointing
+/// up, so yaw drifts slowly since nothing corrects it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MahonyFilter {
+    kp: f32,
+    ki: f32,
+    q: Quaternion,
+    reference: Quaternion,
+    integral: [f32; 3],
+    initialized: bool,
+    last_timestamp: Option<u32>,
+}
+
+impl MahonyFilter {
+    /// - `kp`: how fast the accelerometer corrects the gyroscope drift
+    /// - `ki`: how fast a constant gyroscope bias is learned, 0 to disable
+    pub fn new(kp: f32, ki: f32) -> Self {
+        Self {
+            kp,
+            ki,
+            q: Quaternion::IDENTITY,
+            reference: Quaternion::IDENTITY,
+            integral: [0.0; 3],
+            initialized: false,
+            last_timestamp: None,
+        }
+    }
+
+    /// Feed a motion sample, the time step is taken from the sensor timestamps
+    pub fn update(&mut self, sample: &MotionSample) {
+        let dt = match self.last_timestamp {
+            Some(last) => sample.elapsed_seconds(last),
+            None => 0.0,
+        };
+        self.last_timestamp = Some(sample.timestamp());
+        let gyro = sample.gyro().map(f32::to_radians);
+        self.update_raw(gyro, sample.accel(), dt);
+    }
+
+    /// Feed angular velocity in radians/second and acceleration in any unit, `dt` seconds after the previous
+    /// update. The first update with a valid acceleration aligns the filter with gravity right away
+    pub fn update_raw(&mut self, gyro: [f32; 3], accel: [f32; 3], dt: f32) {
+        let accel_valid = accel.iter().any(|value| *value != 0.0);
+        if !self.initialized {
+            if accel_valid {
+                self.q = Quaternion::from_to(accel, [0.0, 1.0, 0.0]);
+                self.initialized = true;
+            }
+            return;
+        }
+        if dt <= 0.0 || dt > MAX_STEP_SECONDS {
+            return;
+        }
+
+        let mut omega = gyro;
+        if accel_valid {
+            let measured = normalize(accel);
+            let estimated = self.up();
+            let error = cross(measured, estimated);
+            for axis in 0..3 {
+                self.integral[axis] += self.ki * error[axis] * dt;
+                omega[axis] += self.kp * error[axis] + self.integral[axis];
+            }
+        }
+
+        let spin = self.q * Quaternion::new(0.0, omega[0], omega[1], omega[2]);
+        self.q = Quaternion::new(
+            self.q.w + 0.5 * spin.w * dt,
+            self.q.x + 0.5 * spin.x * dt,
+            self.q.y + 0.5 * spin.y * dt,
+            self.q.z + 0.5 * spin.z * dt,
+        )
+        .normalize();
+    }
+
+    /// Orientation relative to the last `Self::recenter` call
+    pub fn orientation(&self) -> Quaternion {
+        self.reference.conjugate() * self.q
+    }
+
+    /// Yaw, pitch and roll relative to the last `Self::recenter` call, in degrees
+    pub fn yaw_pitch_roll(&self) -> (f32, f32, f32) {
+        self.orientation().yaw_pitch_roll()
+    }
+
+    /// Direction of gravity in the controller's frame, in g
+    pub fn gravity(&self) -> [f32; 3] {
+        self.up().map(|value| -value)
+    }
+
+    /// Make the current orientation the neutral one
+    pub fn recenter(&mut self) {
+        self.reference = self.q;
+    }
+
+    /// Up direction in the controller's frame, as currently estimated
+    pub fn up(&self) -> [f32; 3] {
+        self.q.conjugate().rotate([0.0, 1.0, 0.0])
+    }
+}
+
+impl Default for MahonyFilter {
+    fn default() -> Self {
+        Self::new(1.0, 0.05)
+    }
+}
+
+fn cross(l: [f32; 3], r: [f32; 3]) -> [f32; 3] {
+    [
+        l[1] * r[2] - l[2] * r[1],
+        l[2] * r[0] - l[0] * r[2],
+        l[0] * r[1] - l[1] * r[0],
+    ]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let norm = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if norm == 0.0 {
+        return v;
+    }
+    v.map(|value| value / norm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sensor timestamp ticks between two reports at 250 Hz
+    const TICKS: u32 = 12_000;
+    /// Raw accelerometer reading of 1 g with the nominal calibration
+    const ONE_G: i16 = 8192;
+    /// Raw gyroscope reading of about 90 degrees/second with the nominal calibration
+    const RAW_90_DPS: i16 = 1475;
+
+    /// Motion bytes of an input report
+    fn sample(gyro: [i16; 3], accel: [i16; 3], timestamp: u32) -> MotionSample {
+        let mut data = [0; 16];
+        for (axis, value) in gyro.iter().chain(accel.iter()).enumerate() {
+            data[axis * 2..axis * 2 + 2].copy_from_slice(&value.to_le_bytes());
+        }
+        data[12..16].copy_from_slice(&timestamp.to_le_bytes());
+        MotionSample::new(&data)
+    }
+
+    /// Feed `count` identical reports 4 ms apart, returns the timestamp of the next report
+    fn play(
+        filter: &mut MahonyFilter,
+        gyro: [i16; 3],
+        accel: [i16; 3],
+        count: usize,
+        mut timestamp: u32,
+    ) -> u32 {
+        for _ in 0..count {
+            filter.update(&sample(gyro, accel, timestamp));
+            timestamp = timestamp.wrapping_add(TICKS);
+        }
+        timestamp
+    }
+
+    fn degrees_per_second(raw: i16) -> f32 {
+        raw as f32 * 2000.0 / i16::MAX as f32
+    }
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }
+
+    fn assert_angles(filter: &MahonyFilter, expected: (f32, f32, f32), tolerance: f32) {
+        let (yaw, pitch, roll) = filter.yaw_pitch_roll();
+        assert_close(yaw, expected.0, tolerance);
+        assert_close(pitch, expected.1, tolerance);
+        assert_close(roll, expected.2, tolerance);
+    }
+
+    #[test]
+    fn aligns_to_gravity_on_first_sample() {
+        let mut flat = MahonyFilter::default();
+        flat.update(&sample([0; 3], [0, ONE_G, 0], 0));
+        assert_eq!(flat.orientation(), Quaternion::IDENTITY);
+        assert_eq!(flat.gravity(), [0.0, -1.0, 0.0]);
+
+        let mut on_side = MahonyFilter::default();
+        on_side.update(&sample([0; 3], [ONE_G, 0, 0], 0));
+        let gravity = on_side.gravity();
+        assert_close(gravity[0], -1.0, 1e-5);
+        assert_close(gravity[1], 0.0, 1e-5);
+        assert_close(gravity[2], 0.0, 1e-5);
+        assert_angles(&on_side, (0.0, 0.0, 90.0), 1e-3);
+    }
+
+    #[test]
+    fn waits_for_valid_acceleration() {
+        let mut filter = MahonyFilter::default();
+        play(&mut filter, [RAW_90_DPS, 0, 0], [0; 3], 10, 0);
+        assert_eq!(filter.orientation(), Quaternion::IDENTITY);
+    }
+
+    #[test]
+    fn integrates_gyro() {
+        let expected = degrees_per_second(RAW_90_DPS);
+        let traces = [
+            ([0, RAW_90_DPS, 0], (expected, 0.0, 0.0)),
+            ([RAW_90_DPS / 2, 0, 0], (0.0, expected / 2.0, 0.0)),
+            ([0, 0, -RAW_90_DPS / 3], (0.0, 0.0, -expected / 3.0)),
+        ];
+        for (gyro, angles) in traces {
+            // the first report only aligns the filter, the next 250 integrate one second
+            let mut filter = MahonyFilter::new(0.0, 0.0);
+            play(&mut filter, gyro, [0, ONE_G, 0], 251, 0);
+            assert_angles(&filter, angles, 0.1);
+        }
+    }
+
+    #[test]
+    fn handles_timestamp_wrap_and_gaps() {
+        let expected = degrees_per_second(RAW_90_DPS);
+        let mut filter = MahonyFilter::new(0.0, 0.0);
+        let timestamp = play(
+            &mut filter,
+            [0, RAW_90_DPS, 0],
+            [0, ONE_G, 0],
+            126,
+            u32::MAX - 60 * TICKS,
+        );
+        assert_angles(&filter, (expected / 2.0, 0.0, 0.0), 0.1);
+
+        // a report 1 second late is a gap, the rotation during it is lost
+        let timestamp = timestamp.wrapping_add(250 * TICKS);
+        play(&mut filter, [0, RAW_90_DPS, 0], [0, ONE_G, 0], 1, timestamp);
+        assert_angles(&filter, (expected / 2.0, 0.0, 0.0), 0.1);
+    }
+
+    #[test]
+    fn accelerometer_corrects_gyro_bias() {
+        // the gyroscope reports a constant pitch rotation while the accelerometer shows the controller flat
+        let mut filter = MahonyFilter::default();
+        play(&mut filter, [80, 0, 0], [0, ONE_G, 0], 250 * 120, 0);
+        assert_angles(&filter, (0.0, 0.0, 0.0), 0.5);
+
+        let mut uncorrected = MahonyFilter::new(0.0, 0.0);
+        play(&mut uncorrected, [80, 0, 0], [0, ONE_G, 0], 251, 0);
+        assert_close(uncorrected.yaw_pitch_roll().1, degrees_per_second(80), 0.1);
+    }
+
+    #[test]
+    fn recenter() {
+        let expected = degrees_per_second(RAW_90_DPS);
+        let mut filter = MahonyFilter::default();
+        let timestamp = play(&mut filter, [0, RAW_90_DPS, 0], [0, ONE_G, 0], 251, 0);
+        assert_angles(&filter, (expected, 0.0, 0.0), 0.1);
+
+        filter.recenter();
+        assert_angles(&filter, (0.0, 0.0, 0.0), 1e-3);
+
+        play(
+            &mut filter,
+            [0, -RAW_90_DPS, 0],
+            [0, ONE_G, 0],
+            125,
+            timestamp,
+        );
+        assert_angles(&filter, (-expected / 2.0, 0.0, 0.0), 0.1);
+    }
+}
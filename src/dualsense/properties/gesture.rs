@@ -0,0 +1,444 @@
+use super::property::ComboProperty;
+
+/// How fast the gravity estimate follows the accelerometer, in seconds
+const GRAVITY_TIME_CONSTANT: f32 = 0.1;
+/// Reports further apart than this are treated as a gap in the data
+const MAX_STEP_SECONDS: f32 = 0.1;
+
+/// Side of the controller that was lowered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Tilt {
+    Left,
+    Right,
+    /// The side facing away from the player
+    Forward,
+    /// The side facing the player
+    Backward,
+}
+
+/// High level movement of the controller, recognized from the accelerometer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Gesture {
+    #[default]
+    None,
+    /// Shaken back and forth, with the strongest acceleration of the shake in tenths of g, see
+    /// `Self::shake_peak`
+    Shake(u8),
+    /// Tilted past the angle configured for the side to side (roll) or front to back (pitch) axis
+    Tilt(Tilt),
+    /// Turned upside down
+    FaceDown,
+    /// Sharp knock, only reported for a single report
+    Tap,
+}
+
+impl Gesture {
+    /// Strongest acceleration of a shake in g, `None` for the other gestures
+    pub fn shake_peak(&self) -> Option<f32> {
+        match self {
+            Gesture::Shake(tenths) => Some(*tenths as f32 / 10.0),
+            _ => None,
+        }
+    }
+}
+
+impl From<ComboProperty> for Gesture {
+    fn from(value: ComboProperty) -> Self {
+        match value {
+            ComboProperty::Gesture(v) => v,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Recognizes gestures from accelerometer readings. When several gestures happen at once, a shake wins over a
+/// tap, which wins over a flip, which wins over a tilt
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureRecognizer {
+    shake_threshold: f32,
+    shake_peaks: u8,
+    shake_seconds: f32,
+    tilt_roll_degrees: f32,
+    tilt_pitch_degrees: f32,
+    tap_threshold: f32,
+    tap_cooldown_seconds: f32,
+    gravity: Option<[f32; 3]>,
+    last_accel: [f32; 3],
+    last_peak: Option<[f32; 3]>,
+    peaks: u8,
+    strongest_peak: f32,
+    since_peak: f32,
+    since_tap: f32,
+    current: Gesture,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self {
+            shake_threshold: 1.5,
+            shake_peaks: 4,
+            shake_seconds: 0.4,
+            tilt_roll_degrees: 40.0,
+            tilt_pitch_degrees: 40.0,
+            tap_threshold: 1.5,
+            tap_cooldown_seconds: 0.2,
+            gravity: None,
+            last_accel: [0.0; 3],
+            last_peak: None,
+            peaks: 0,
+            strongest_peak: 0.0,
+            since_peak: 0.0,
+            since_tap: 0.0,
+            current: Gesture::None,
+        }
+    }
+
+    /// A shake is `peaks` accelerations stronger than `threshold` g, each in the opposite direction of the previous
+    /// one and less than `seconds` apart
+    pub fn shake(mut self, threshold: f32, peaks: u8, seconds: f32) -> Self {
+        self.shake_threshold = threshold;
+        self.shake_peaks = peaks.max(2);
+        self.shake_seconds = seconds;
+        self
+    }
+
+    /// Angle from lying flat past which the controller is tilted, in degrees, the same for both axes
+    pub fn tilt(self, degrees: f32) -> Self {
+        self.tilt_axes(degrees, degrees)
+    }
+
+    /// Angles from lying flat past which the controller is tilted left or right (`roll_degrees`) and forward or
+    /// backward (`pitch_degrees`). When both are passed the larger angle wins
+    pub fn tilt_axes(mut self, roll_degrees: f32, pitch_degrees: f32) -> Self {
+        self.tilt_roll_degrees = roll_degrees.clamp(0.0, 90.0);
+        self.tilt_pitch_degrees = pitch_degrees.clamp(0.0, 90.0);
+        self
+    }
+
+    /// A tap is a change of acceleration stronger than `threshold` g between two reports, taps closer than
+    /// `cooldown_seconds` are ignored
+    pub fn tap(mut self, threshold: f32, cooldown_seconds: f32) -> Self {
+        self.tap_threshold = threshold;
+        self.tap_cooldown_seconds = cooldown_seconds;
+        self
+    }
+
+    /// Feed the acceleration in g and the seconds elapsed since the previous update
+    pub fn update(&mut self, accel: [f32; 3], dt: f32) -> Gesture {
+        let dt = dt.clamp(0.0, MAX_STEP_SECONDS);
+        let gravity = match self.gravity {
+            Some(gravity) => {
+                let weight = (dt / GRAVITY_TIME_CONSTANT).min(1.0);
+                [0, 1, 2].map(|axis| gravity[axis] + (accel[axis] - gravity[axis]) * weight)
+            }
+            None => accel,
+        };
+        let first = self.gravity.is_none();
+        self.gravity = Some(gravity);
+
+        let shaking = self.update_shake(accel, gravity, dt);
+        let tapped = !first && self.update_tap(accel, dt);
+        self.last_accel = accel;
+
+        self.current = if shaking {
+            Gesture::Shake(
+                (self.strongest_peak * 10.0)
+                    .round()
+                    .clamp(1.0, u8::MAX as f32) as u8,
+            )
+        } else if tapped {
+            Gesture::Tap
+        } else {
+            self.orientation_gesture(gravity)
+        };
+        self.current
+    }
+
+    pub fn current(&self) -> Gesture {
+        self.current
+    }
+
+    fn update_shake(&mut self, accel: [f32; 3], gravity: [f32; 3], dt: f32) -> bool {
+        let dynamic = [0, 1, 2].map(|axis| accel[axis] - gravity[axis]);
+        let strength = length(dynamic);
+        let reversed = self.last_peak.is_none_or(|last| dot(last, dynamic) < 0.0);
+
+        if strength > self.shake_threshold && reversed {
+            self.peaks = self.peaks.saturating_add(1);
+            self.last_peak = Some(dynamic);
+            self.since_peak = 0.0;
+        } else {
+            self.since_peak += dt;
+            if self.since_peak > self.shake_seconds {
+                self.peaks = 0;
+                self.strongest_peak = 0.0;
+                self.last_peak = None;
+            }
+        }
+        // a swing keeps getting stronger after the sample that counted it as a peak
+        if self.peaks > 0 && strength > self.shake_threshold {
+            self.strongest_peak = self.strongest_peak.max(strength);
+        }
+        self.peaks >= self.shake_peaks
+    }
+
+    fn update_tap(&mut self, accel: [f32; 3], dt: f32) -> bool {
+        self.since_tap += dt;
+        let jerk = length([0, 1, 2].map(|axis| accel[axis] - self.last_accel[axis]));
+        if jerk > self.tap_threshold && self.since_tap > self.tap_cooldown_seconds {
+            self.since_tap = 0.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Flip and tilt, from the direction gravity pulls in. The accelerometer points up while resting, Y being
+    /// up when the controller lies flat
+    fn orientation_gesture(&self, up: [f32; 3]) -> Gesture {
+        let norm = length(up);
+        if norm == 0.0 {
+            return Gesture::None;
+        }
+        let up = up.map(|value| value / norm);
+        if up[1] < -(45_f32.to_radians().cos()) {
+            return Gesture::FaceDown;
+        }
+
+        let roll = (-up[0]).atan2(up[1]).to_degrees();
+        let pitch = up[2].atan2(up[1]).to_degrees();
+        let rolled = roll.abs() >= self.tilt_roll_degrees;
+        let pitched = pitch.abs() >= self.tilt_pitch_degrees;
+        if rolled && (!pitched || roll.abs() >= pitch.abs()) {
+            Gesture::Tilt(if roll > 0.0 { Tilt::Right } else { Tilt::Left })
+        } else if pitched {
+            Gesture::Tilt(if pitch > 0.0 {
+                Tilt::Forward
+            } else {
+                Tilt::Backward
+            })
+        } else {
+            Gesture::None
+        }
+    }
+}
+
+impl Default for GestureRecognizer {
+    /// Shakes of 4 peaks over 1.5 g, tilts past 40 degrees and taps over 1.5 g
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn dot(l: [f32; 3], r: [f32; 3]) -> f32 {
+    l[0] * r[0] + l[1] * r[1] + l[2] * r[2]
+}
+
+fn length(v: [f32; 3]) -> f32 {
+    dot(v, v).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    /// Seconds between two reports at 250 Hz
+    const DT: f32 = 0.004;
+    const FLAT: [f32; 3] = [0.0, 1.0, 0.0];
+
+    /// Gestures recognized for each sample of an accelerometer trace
+    fn run(
+        recognizer: &mut GestureRecognizer,
+        trace: impl IntoIterator<Item = [f32; 3]>,
+    ) -> Vec<Gesture> {
+        trace
+            .into_iter()
+            .map(|accel| recognizer.update(accel, DT))
+            .collect()
+    }
+
+    /// Controller resting for `seconds`, with some sensor noise
+    fn resting(seconds: f32) -> Vec<[f32; 3]> {
+        let noise = [0.02, -0.01, 0.0, 0.015, -0.02];
+        (0..(seconds / DT) as usize)
+            .map(|i| {
+                let n = noise[i % noise.len()];
+                [n, 1.0 - n, -n]
+            })
+            .collect()
+    }
+
+    /// Gravity rotating from lying flat to `degrees` over `seconds`, `towards` being the axis that ends up
+    /// pointing down
+    fn rotate(degrees: f32, seconds: f32, towards: [f32; 3]) -> Vec<[f32; 3]> {
+        let samples = (seconds / DT) as usize;
+        (0..=samples)
+            .map(|i| {
+                let angle = (degrees * i as f32 / samples as f32).to_radians();
+                [0, 1, 2].map(|axis| FLAT[axis] * angle.cos() - towards[axis] * angle.sin())
+            })
+            .collect()
+    }
+
+    /// Shaking along X with a sine of `amplitude` g at `frequency` Hz for `seconds`
+    fn shaking(amplitude: f32, frequency: f32, seconds: f32) -> Vec<[f32; 3]> {
+        (0..(seconds / DT) as usize)
+            .map(|i| {
+                [
+                    amplitude * (2.0 * PI * frequency * i as f32 * DT).sin(),
+                    1.0,
+                    0.0,
+                ]
+            })
+            .collect()
+    }
+
+    fn spike(g: f32) -> Vec<[f32; 3]> {
+        vec![[0.0, 1.0 + g, 0.0], FLAT]
+    }
+
+    #[test]
+    fn nothing_while_resting() {
+        let mut recognizer = GestureRecognizer::default();
+        let gestures = run(&mut recognizer, resting(2.0));
+        assert!(gestures.iter().all(|gesture| *gesture == Gesture::None));
+    }
+
+    #[test]
+    fn tilt() {
+        let traces = [
+            ([1.0, 0.0, 0.0], Tilt::Right),
+            ([-1.0, 0.0, 0.0], Tilt::Left),
+            ([0.0, 0.0, -1.0], Tilt::Forward),
+            ([0.0, 0.0, 1.0], Tilt::Backward),
+        ];
+        for (towards, expected) in traces {
+            let mut recognizer = GestureRecognizer::default();
+            run(&mut recognizer, resting(0.5));
+            let gestures = run(&mut recognizer, rotate(60.0, 1.0, towards));
+            // the gravity estimate lags slightly behind the rotation
+            let first = gestures
+                .iter()
+                .position(|gesture| *gesture != Gesture::None)
+                .unwrap();
+            assert!((190..=215).contains(&first), "tilted after {first} samples");
+            assert!(gestures[first..]
+                .iter()
+                .all(|gesture| *gesture == Gesture::Tilt(expected)));
+        }
+    }
+
+    #[test]
+    fn small_tilt_is_ignored() {
+        let mut recognizer = GestureRecognizer::default().tilt(40.0);
+        run(&mut recognizer, resting(0.5));
+        let gestures = run(&mut recognizer, rotate(30.0, 1.0, [1.0, 0.0, 0.0]));
+        assert!(gestures.iter().all(|gesture| *gesture == Gesture::None));
+    }
+
+    #[test]
+    fn tilt_thresholds_per_axis() {
+        let tilted = |towards, degrees| {
+            let mut recognizer = GestureRecognizer::default().tilt_axes(20.0, 60.0);
+            run(&mut recognizer, resting(0.5));
+            let trace = rotate(degrees, 1.0, towards);
+            let held = *trace.last().unwrap();
+            run(&mut recognizer, trace);
+            // let the gravity estimate catch up
+            run(&mut recognizer, vec![held; 100]);
+            recognizer.current()
+        };
+        assert_eq!(tilted([1.0, 0.0, 0.0], 30.0), Gesture::Tilt(Tilt::Right));
+        assert_eq!(tilted([0.0, 0.0, -1.0], 30.0), Gesture::None);
+        assert_eq!(tilted([0.0, 0.0, -1.0], 70.0), Gesture::Tilt(Tilt::Forward));
+    }
+
+    #[test]
+    fn flip() {
+        let mut recognizer = GestureRecognizer::default();
+        run(&mut recognizer, resting(0.5));
+        let gestures = run(&mut recognizer, rotate(180.0, 1.0, [1.0, 0.0, 0.0]));
+        assert!(gestures.contains(&Gesture::Tilt(Tilt::Right)));
+        assert_eq!(gestures.last(), Some(&Gesture::FaceDown));
+        assert!(!gestures.contains(&Gesture::Tap));
+
+        let gestures = run(&mut recognizer, vec![[0.0, -1.0, 0.0]; 100]);
+        assert!(gestures.iter().all(|gesture| *gesture == Gesture::FaceDown));
+    }
+
+    #[test]
+    fn shake() {
+        let mut recognizer = GestureRecognizer::default();
+        run(&mut recognizer, resting(0.5));
+        let gestures = run(&mut recognizer, shaking(2.5, 5.0, 1.0));
+        // 4 half periods of 0.1 seconds are needed
+        let first = gestures
+            .iter()
+            .position(|gesture| matches!(gesture, Gesture::Shake(_)))
+            .unwrap();
+        assert!(
+            (300 / 4..=400 / 4).contains(&first),
+            "shaken after {first} samples"
+        );
+        assert!(gestures[first..].iter().all(|gesture| gesture
+            .shake_peak()
+            .is_some_and(|peak| (1.5..=2.5).contains(&peak))));
+        assert_eq!(Gesture::Shake(27).shake_peak(), Some(2.7));
+        assert_eq!(Gesture::Tap.shake_peak(), None);
+
+        // the peak keeps its tenths of g
+        let mut stronger = GestureRecognizer::default();
+        run(&mut stronger, resting(0.5));
+        let peak = run(&mut stronger, shaking(3.5, 5.0, 1.0))
+            .last()
+            .and_then(Gesture::shake_peak)
+            .unwrap();
+        let weaker = gestures.last().and_then(Gesture::shake_peak).unwrap();
+        assert!(peak - weaker >= 0.5, "{weaker} then {peak}");
+
+        // the shake ends once no peak came for 0.4 seconds
+        let gestures = run(&mut recognizer, resting(1.0));
+        assert!(matches!(gestures[0], Gesture::Shake(_)));
+        assert_eq!(gestures.last(), Some(&Gesture::None));
+    }
+
+    #[test]
+    fn weak_or_slow_shake_is_ignored() {
+        let mut recognizer = GestureRecognizer::default();
+        run(&mut recognizer, resting(0.5));
+        let weak = run(&mut recognizer, shaking(1.0, 5.0, 1.0));
+        assert!(!weak
+            .iter()
+            .any(|gesture| matches!(gesture, Gesture::Shake(_))));
+
+        let mut recognizer = GestureRecognizer::default();
+        run(&mut recognizer, resting(0.5));
+        let slow = run(&mut recognizer, shaking(2.5, 0.5, 4.0));
+        assert!(!slow
+            .iter()
+            .any(|gesture| matches!(gesture, Gesture::Shake(_))));
+    }
+
+    #[test]
+    fn tap_and_cooldown() {
+        let mut recognizer = GestureRecognizer::default();
+        run(&mut recognizer, resting(0.5));
+        let gestures = run(&mut recognizer, spike(2.0));
+        assert_eq!(gestures, [Gesture::Tap, Gesture::None]);
+
+        // 0.1 seconds later, still cooling down
+        run(&mut recognizer, resting(0.1));
+        let gestures = run(&mut recognizer, spike(2.0));
+        assert!(!gestures.contains(&Gesture::Tap));
+
+        run(&mut recognizer, resting(0.3));
+        let gestures = run(&mut recognizer, spike(2.0));
+        assert_eq!(gestures[0], Gesture::Tap);
+
+        run(&mut recognizer, resting(0.3));
+        let gestures = run(&mut recognizer, spike(1.2));
+        assert!(!gestures.contains(&Gesture::Tap));
+    }
+}
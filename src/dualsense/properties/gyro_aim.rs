@@ -0,0 +1,314 @@
+use super::{analog_pad::AnalogPad, property::ComboProperty};
+
+/// Reports further apart than this are treated as a gap in the data and produce no movement
+const MAX_STEP_SECONDS: f32 = 0.1;
+/// How much of the world yaw is kept in `GyroSpace::Player` before falling back to the local yaw and roll
+const PLAYER_SPACE_RELAX: f32 = 1.41;
+
+/// Which rotations of the controller turn the camera left and right
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GyroSpace {
+    /// Rotation around the controller's own up axis, no matter how it is held
+    #[default]
+    Local,
+    /// Rotation around the gravity axis, pitch around the horizontal axis. Needs the controller's orientation
+    World,
+    /// Rotation around the gravity axis blended with the local yaw and roll, forgiving of how the controller is
+    /// held. Needs the controller's orientation
+    Player,
+}
+
+/// Turns gyroscope readings into camera movement, in degrees. The deltas point right and up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GyroAim {
+    space: GyroSpace,
+    sensitivity: f32,
+    max_sensitivity: f32,
+    acceleration_speeds: (f32, f32),
+    smoothing_speed: f32,
+    smoothing_seconds: f32,
+    tightening_speed: f32,
+    ratchet: Option<ComboProperty>,
+    smoothed: (f32, f32),
+}
+
+impl GyroAim {
+    /// `sensitivity`: degrees the camera turns for each degree the controller turns
+    pub fn new(sensitivity: f32) -> Self {
+        Self {
+            space: GyroSpace::default(),
+            sensitivity,
+            max_sensitivity: sensitivity,
+            acceleration_speeds: (0.0, 0.0),
+            smoothing_speed: 0.0,
+            smoothing_seconds: 0.0,
+            tightening_speed: 0.0,
+            ratchet: None,
+            smoothed: (0.0, 0.0),
+        }
+    }
+
+    pub fn space(mut self, space: GyroSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Raise the sensitivity up to `max_sensitivity` as the controller turns faster, starting from `slow_speed`
+    /// and reaching it at `fast_speed`, in degrees/second
+    pub fn acceleration(mut self, max_sensitivity: f32, slow_speed: f32, fast_speed: f32) -> Self {
+        self.max_sensitivity = max_sensitivity;
+        self.acceleration_speeds = (slow_speed.min(fast_speed), slow_speed.max(fast_speed));
+        self
+    }
+
+    /// Average movements slower than `speed` degrees/second over about the last `seconds`, to hide the sensor
+    /// noise while keeping fast movements responsive. The average follows time rather than reports, so it feels
+    /// the same whatever rate the controller sends reports at
+    pub fn smoothing(mut self, speed: f32, seconds: f32) -> Self {
+        self.smoothing_speed = speed.max(0.0);
+        self.smoothing_seconds = seconds.max(0.0);
+        self
+    }
+
+    /// Scale down movements slower than `speed` degrees/second, making small shakes of the hands disappear
+    pub fn tightening(mut self, speed: f32) -> Self {
+        self.tightening_speed = speed.max(0.0);
+        self
+    }
+
+    /// Stop moving the camera while the input equals `button`, for example `ComboProperty::RB(true)`, to
+    /// reposition the controller like lifting a mouse
+    pub fn ratchet(mut self, button: ComboProperty) -> Self {
+        self.ratchet = Some(button);
+        self
+    }
+
+    pub fn ratchet_button(&self) -> Option<ComboProperty> {
+        self.ratchet
+    }
+
+    /// Feed the angular velocity in degrees/second, the up direction in the controller's frame (only used by
+    /// the world and player spaces) and the seconds elapsed since the previous update. Returns the camera movement
+    /// in degrees, pointing right and up
+    pub fn update(
+        &mut self,
+        gyro: [f32; 3],
+        up: [f32; 3],
+        dt: f32,
+        ratchet_held: bool,
+    ) -> (f32, f32) {
+        if dt <= 0.0 || dt > MAX_STEP_SECONDS {
+            return (0.0, 0.0);
+        }
+        let (yaw, pitch) = self.rotation_rates(gyro, up);
+
+        let speed = yaw.hypot(pitch);
+        let (yaw, pitch) = self.smooth(yaw, pitch, speed, dt);
+        let tightening = if speed < self.tightening_speed {
+            speed / self.tightening_speed
+        } else {
+            1.0
+        };
+        let sensitivity = self.sensitivity_at(speed) * tightening;
+
+        if ratchet_held {
+            (0.0, 0.0)
+        } else {
+            (yaw * sensitivity * dt, pitch * sensitivity * dt)
+        }
+    }
+
+    /// Yaw rate pointing right and pitch rate pointing up, in degrees/second
+    fn rotation_rates(&self, gyro: [f32; 3], up: [f32; 3]) -> (f32, f32) {
+        match self.space {
+            GyroSpace::Local => (-gyro[1], gyro[0]),
+            GyroSpace::World => {
+                let yaw = gyro[0] * up[0] + gyro[1] * up[1] + gyro[2] * up[2];
+                let pitch_axis = [1.0 - up[0] * up[0], -up[1] * up[0], -up[2] * up[0]];
+                let length = (pitch_axis[0] * pitch_axis[0]
+                    + pitch_axis[1] * pitch_axis[1]
+                    + pitch_axis[2] * pitch_axis[2])
+                    .sqrt();
+                let pitch = if length == 0.0 {
+                    0.0
+                } else {
+                    (gyro[0] * pitch_axis[0] + gyro[1] * pitch_axis[1] + gyro[2] * pitch_axis[2])
+                        / length
+                };
+                (-yaw, pitch)
+            }
+            GyroSpace::Player => {
+                let world_yaw = gyro[1] * up[1] + gyro[2] * up[2];
+                let yaw = (world_yaw.abs() * PLAYER_SPACE_RELAX).min(gyro[1].hypot(gyro[2]));
+                (-yaw.copysign(world_yaw), gyro[0])
+            }
+        }
+    }
+
+    /// Split the movement between its direct part and its smoothed part depending on its speed
+    fn smooth(&mut self, yaw: f32, pitch: f32, speed: f32, dt: f32) -> (f32, f32) {
+        let direct = if self.smoothing_speed == 0.0 {
+            1.0
+        } else {
+            let half = self.smoothing_speed / 2.0;
+            ((speed - half) / half).clamp(0.0, 1.0)
+        };
+
+        // exponential average with a time constant of `smoothing_seconds`
+        let weight = if self.smoothing_seconds == 0.0 {
+            1.0
+        } else {
+            (dt / self.smoothing_seconds).min(1.0)
+        };
+        self.smoothed.0 += (yaw * (1.0 - direct) - self.smoothed.0) * weight;
+        self.smoothed.1 += (pitch * (1.0 - direct) - self.smoothed.1) * weight;
+
+        (
+            yaw * direct + self.smoothed.0,
+            pitch * direct + self.smoothed.1,
+        )
+    }
+
+    fn sensitivity_at(&self, speed: f32) -> f32 {
+        let (slow, fast) = self.acceleration_speeds;
+        let blend = if fast > slow {
+            ((speed - slow) / (fast - slow)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self.sensitivity + (self.max_sensitivity - self.sensitivity) * blend
+    }
+}
+
+impl Default for GyroAim {
+    /// Camera turning as much as the controller, in the local space
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Turns the camera to face where the stick points: pushing the stick flicks the camera towards its direction,
+/// rotating the stick while it is held turns the camera by the same angle. Movement is in degrees, pointing
+/// right
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlickStick {
+    threshold: f32,
+    flick_seconds: f32,
+    last_angle: Option<f32>,
+    flick_rate: f32,
+    flick_remaining: f32,
+}
+
+impl FlickStick {
+    /// - `threshold`: distance of the stick from its center at which a flick starts, in the [0, 1] interval
+    /// - `flick_seconds`: how long the flick takes, 0 to turn instantly
+    pub fn new(threshold: f32, flick_seconds: f32) -> Self {
+        Self {
+            threshold: threshold.clamp(0.0, 1.0),
+            flick_seconds: flick_seconds.max(0.0),
+            last_angle: None,
+            flick_rate: 0.0,
+            flick_remaining: 0.0,
+        }
+    }
+
+    /// Feed the stick position and the seconds elapsed since the previous update, returns how much the camera
+    /// turns, in degrees
+    pub fn update(&mut self, pad: &AnalogPad, dt: f32) -> f32 {
+        let mut turn = 0.0;
+        if pad.magnitude() < self.threshold {
+            self.last_angle = None;
+        } else {
+            let angle = pad.angle_degrees();
+            match self.last_angle {
+                None => {
+                    self.flick_remaining = wrap_degrees(angle);
+                    self.flick_rate = if self.flick_seconds == 0.0 {
+                        f32::INFINITY
+                    } else {
+                        self.flick_remaining.abs() / self.flick_seconds
+                    };
+                }
+                Some(last) => turn += wrap_degrees(angle - last),
+            }
+            self.last_angle = Some(angle);
+        }
+
+        if self.flick_remaining != 0.0 {
+            let step = self
+                .flick_remaining
+                .abs()
+                .min(self.flick_rate * dt.max(f32::EPSILON));
+            let step = step.copysign(self.flick_remaining);
+            self.flick_remaining -= step;
+            turn += step;
+        }
+        turn
+    }
+}
+
+impl Default for FlickStick {
+    /// Flick once the stick is pushed 90% of the way, over 0.1 seconds
+    fn default() -> Self {
+        Self::new(0.9, 0.1)
+    }
+}
+
+/// Wrap an angle to the (-180, 180] interval
+fn wrap_degrees(angle: f32) -> f32 {
+    let wrapped = angle.rem_euclid(360.0);
+    if wrapped > 180.0 {
+        wrapped - 360.0
+    } else {
+        wrapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UP: [f32; 3] = [0.0, 1.0, 0.0];
+
+    /// Camera movement after turning the controller at `speed` degrees/second around its up axis for one
+    /// second, at `rate` reports per second
+    fn turn(aim: &mut GyroAim, speed: f32, rate: u32) -> (f32, f32) {
+        let dt = 1.0 / rate as f32;
+        (0..rate).fold((0.0, 0.0), |total, _| {
+            let (x, y) = aim.update([0.0, -speed, 0.0], UP, dt, false);
+            (total.0 + x, total.1 + y)
+        })
+    }
+
+    #[test]
+    fn follows_the_controller() {
+        let (x, y) = turn(&mut GyroAim::new(2.0), 90.0, 250);
+        assert!((x - 180.0).abs() < 0.01, "{x}");
+        assert_eq!(y, 0.0);
+    }
+
+    #[test]
+    fn ratchet_stops_the_camera() {
+        let mut aim = GyroAim::default().ratchet(ComboProperty::RB(true));
+        assert_eq!(aim.ratchet_button(), Some(ComboProperty::RB(true)));
+        assert_eq!(aim.update([10.0, 20.0, 0.0], UP, 0.004, true), (0.0, 0.0));
+    }
+
+    #[test]
+    fn independent_of_the_report_rate() {
+        let aim = GyroAim::new(1.0)
+            .acceleration(3.0, 20.0, 200.0)
+            .smoothing(10.0, 0.1)
+            .tightening(2.0);
+        for speed in [1.5, 8.0, 60.0, 400.0] {
+            let expected = turn(&mut aim.clone(), speed, 1000).0;
+            for rate in [250, 500] {
+                let x = turn(&mut aim.clone(), speed, rate).0;
+                assert!(
+                    (x - expected).abs() <= expected * 0.02,
+                    "{x} != {expected} at {speed} degrees/second and {rate} Hz"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Largest gyroscope bias `GyroBiasEstimator` accepts by default, in degrees/second
+pub const DEFAULT_MAX_BIAS: f32 = 3.0;
+
+/// Angular velocity the gyroscope reports while the controller is not moving, in degrees/second. It is
+/// subtracted from every reading
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GyroBias {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl GyroBias {
+    /// Remove the bias from a reading in degrees/second
+    pub fn apply(&self, gyro: [f32; 3]) -> [f32; 3] {
+        [gyro[0] - self.x, gyro[1] - self.y, gyro[2] - self.z]
+    }
+
+    fn from_array(values: [f32; 3]) -> Self {
+        Self {
+            x: values[0],
+            y: values[1],
+            z: values[2],
+        }
+    }
+
+    fn to_array(self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+/// Averages gyroscope readings taken while the controller is left still, to build a `GyroBias`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GyroCalibrator {
+    sum: [f64; 3],
+    samples: u32,
+}
+
+impl GyroCalibrator {
+    /// Add a reading in degrees/second, without any bias removed
+    pub fn sample(&mut self, gyro: [f32; 3]) {
+        for (sum, value) in self.sum.iter_mut().zip(gyro) {
+            *sum += value as f64;
+        }
+        self.samples += 1;
+    }
+
+    /// Build the bias, no bias if nothing was sampled
+    pub fn finish(&self) -> GyroBias {
+        if self.samples == 0 {
+            return GyroBias::default();
+        }
+        GyroBias::from_array(self.sum.map(|sum| (sum / self.samples as f64) as f32))
+    }
+}
+
+/// Keeps the gyroscope bias up to date by detecting when the controller rests. Readings are grouped in windows;
+/// when both sensors barely vary over a whole window and the average angular velocity is small enough to be a
+/// bias, the controller is at rest and the bias moves towards that average. The cap keeps a steady rotation
+/// around the gravity axis, which leaves the accelerometer unchanged, from being learned as bias
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GyroBiasEstimator {
+    window: u32,
+    gyro_noise: f32,
+    accel_noise: f32,
+    smoothing: f32,
+    max_bias: f32,
+    gyro_stats: [AxisStats; 3],
+    accel_stats: [AxisStats; 3],
+    at_rest: bool,
+    bias: Option<GyroBias>,
+}
+
+impl GyroBiasEstimator {
+    /// - `window`: number of readings checked together
+    /// - `gyro_noise`: standard deviation of the gyroscope under which it is still, in degrees/second
+    /// - `accel_noise`: standard deviation of the accelerometer under which it is still, in g
+    /// - `smoothing`: weight of each window at rest in the bias, in the (0, 1] interval
+    pub fn new(window: u32, gyro_noise: f32, accel_noise: f32, smoothing: f32) -> Self {
+        Self {
+            window: window.max(2),
+            gyro_noise,
+            accel_noise,
+            smoothing: smoothing.clamp(f32::EPSILON, 1.0),
+            max_bias: DEFAULT_MAX_BIAS,
+            gyro_stats: Default::default(),
+            accel_stats: Default::default(),
+            at_rest: false,
+            bias: None,
+        }
+    }
+
+    /// Largest average angular velocity accepted as bias, in degrees/second. Windows turning faster are not at
+    /// rest, however steady they are
+    pub fn max_bias(mut self, max_bias: f32) -> Self {
+        self.max_bias = max_bias;
+        self
+    }
+
+    /// Feed a reading: angular velocity in degrees/second, without any bias removed, and acceleration in g
+    pub fn update(&mut self, gyro: [f32; 3], accel: [f32; 3]) {
+        for (stats, value) in self.gyro_stats.iter_mut().zip(gyro) {
+            stats.add(value);
+        }
+        for (stats, value) in self.accel_stats.iter_mut().zip(accel) {
+            stats.add(value);
+        }
+        if self.gyro_stats[0].count < self.window {
+            return;
+        }
+
+        let mean = self.gyro_stats.map(|s| s.mean());
+        self.at_rest = self
+            .gyro_stats
+            .iter()
+            .all(|s| s.deviation() <= self.gyro_noise)
+            && self
+                .accel_stats
+                .iter()
+                .all(|s| s.deviation() <= self.accel_noise)
+            && mean.iter().map(|m| m * m).sum::<f32>().sqrt() <= self.max_bias;
+        if self.at_rest {
+            self.bias = Some(match self.bias {
+                Some(bias) => {
+                    let bias = bias.to_array();
+                    GyroBias::from_array(
+                        [0, 1, 2]
+                            .map(|axis| bias[axis] + (mean[axis] - bias[axis]) * self.smoothing),
+                    )
+                }
+                None => GyroBias::from_array(mean),
+            });
+        }
+        self.gyro_stats = Default::default();
+        self.accel_stats = Default::default();
+    }
+
+    /// Current estimate, no bias until the controller was seen at rest
+    pub fn bias(&self) -> GyroBias {
+        self.bias.unwrap_or_default()
+    }
+
+    /// Start from a known bias, for example one stored from a previous session
+    pub fn set_bias(&mut self, bias: GyroBias) {
+        self.bias = Some(bias);
+    }
+
+    /// Whether the last complete window was at rest
+    pub fn is_at_rest(&self) -> bool {
+        self.at_rest
+    }
+}
+
+impl Default for GyroBiasEstimator {
+    /// Windows of 50 readings, still under 0.5 degrees/second and 0.01 g of noise, with a bias of at most
+    /// `DEFAULT_MAX_BIAS`
+    fn default() -> Self {
+        Self::new(50, 0.5, 0.01, 0.2)
+    }
+}
+
+/// Running mean and variance of a single axis
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct AxisStats {
+    count: u32,
+    sum: f64,
+    sum_squares: f64,
+}
+
+impl AxisStats {
+    fn add(&mut self, value: f32) {
+        self.count += 1;
+        self.sum += value as f64;
+        self.sum_squares += value as f64 * value as f64;
+    }
+
+    fn mean(&self) -> f32 {
+        (self.sum / self.count.max(1) as f64) as f32
+    }
+
+    fn deviation(&self) -> f32 {
+        let count = self.count.max(1) as f64;
+        let mean = self.sum / count;
+        (self.sum_squares / count - mean * mean).max(0.0).sqrt() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Controller lying flat, gravity along Y
+    const FLAT: [f32; 3] = [0.0, 1.0, 0.0];
+
+    fn feed(estimator: &mut GyroBiasEstimator, readings: u32, gyro: impl Fn(u32) -> [f32; 3]) {
+        for i in 0..readings {
+            estimator.update(gyro(i), FLAT);
+        }
+    }
+
+    fn assert_bias(bias: GyroBias, expected: [f32; 3]) {
+        let bias = bias.to_array();
+        for axis in 0..3 {
+            assert!(
+                (bias[axis] - expected[axis]).abs() < 1e-3,
+                "{bias:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn calibrator_averages_readings() {
+        let mut calibrator = GyroCalibrator::default();
+        assert_eq!(calibrator.finish(), GyroBias::default());
+        calibrator.sample([1.0, 2.0, -1.0]);
+        calibrator.sample([3.0, 0.0, -1.0]);
+        assert_bias(calibrator.finish(), [2.0, 1.0, -1.0]);
+    }
+
+    #[test]
+    fn constant_offset_at_rest_converges() {
+        let offset = [0.8, -0.4, 0.2];
+        let mut estimator = GyroBiasEstimator::default().max_bias(2.0);
+        estimator.set_bias(GyroBias::default());
+        // sensor noise well under the threshold
+        let jitter = |i: u32| if i.is_multiple_of(2) { 0.05 } else { -0.05 };
+        feed(&mut estimator, 50 * 40, |i| offset.map(|o| o + jitter(i)));
+        assert!(estimator.is_at_rest());
+        assert_bias(estimator.bias(), offset);
+    }
+
+    #[test]
+    fn first_window_at_rest_sets_the_bias() {
+        let mut estimator = GyroBiasEstimator::default();
+        feed(&mut estimator, 49, |_| [1.0, 0.0, 0.0]);
+        assert_eq!(estimator.bias(), GyroBias::default());
+        feed(&mut estimator, 1, |_| [1.0, 0.0, 0.0]);
+        assert_bias(estimator.bias(), [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn noisy_readings_are_rejected() {
+        let mut estimator = GyroBiasEstimator::default();
+        feed(&mut estimator, 50 * 10, |i| {
+            let swing = if i.is_multiple_of(2) { 2.0 } else { -2.0 };
+            [0.5 + swing, 0.0, 0.0]
+        });
+        assert!(!estimator.is_at_rest());
+        assert_eq!(estimator.bias(), GyroBias::default());
+
+        // still gyroscope, moving accelerometer
+        let mut estimator = GyroBiasEstimator::default();
+        for i in 0..500_u32 {
+            let tilt = if i.is_multiple_of(2) { 0.1 } else { -0.1 };
+            estimator.update([0.5, 0.0, 0.0], [tilt, 1.0, 0.0]);
+        }
+        assert!(!estimator.is_at_rest());
+        assert_eq!(estimator.bias(), GyroBias::default());
+    }
+
+    #[test]
+    fn constant_rotation_is_rejected() {
+        // turning steadily around the gravity axis, like on a turntable, keeps both sensors still
+        let mut estimator = GyroBiasEstimator::default();
+        feed(&mut estimator, 50 * 10, |_| [0.0, 20.0, 0.0]);
+        assert!(!estimator.is_at_rest());
+        assert_eq!(estimator.bias(), GyroBias::default());
+
+        // a learned bias is kept through the rotation
+        estimator.set_bias(GyroBias::from_array([0.3, 0.1, 0.0]));
+        feed(&mut estimator, 50 * 10, |_| [0.0, -4.0, 0.0]);
+        assert_bias(estimator.bias(), [0.3, 0.1, 0.0]);
+    }
+}
@@ -0,0 +1,422 @@
+use std::{
+    cmp::Reverse,
+    sync::{Arc, Mutex},
+};
+
+use super::rumble::Rumble;
+
+/// Shape of a pulse over time: the intensity rises from 0 to its peak over `attack` seconds, stays there for
+/// `sustain` seconds and falls back to 0 over `decay` seconds
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,
+    pub sustain: f32,
+    pub decay: f32,
+}
+
+impl Envelope {
+    pub fn new(attack: f32, sustain: f32, decay: f32) -> Self {
+        Self {
+            attack: attack.max(0.0),
+            sustain: sustain.max(0.0),
+            decay: decay.max(0.0),
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.attack + self.sustain + self.decay
+    }
+
+    /// Fraction of the peak intensity `seconds` after the pulse started, in the [0, 1] interval
+    pub fn level_at(&self, seconds: f32) -> f32 {
+        if seconds < 0.0 || seconds >= self.duration() {
+            0.0
+        } else if seconds < self.attack {
+            seconds / self.attack
+        } else if seconds < self.attack + self.sustain {
+            1.0
+        } else {
+            1.0 - (seconds - self.attack - self.sustain) / self.decay.max(f32::EPSILON)
+        }
+    }
+}
+
+/// Peak motor intensities shaped by an envelope, starting some time after the effect starts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HapticPulse {
+    pub start: f32,
+    pub rumble: Rumble,
+    pub envelope: Envelope,
+}
+
+/// Sequence of pulses over the rumble motors. Overlapping pulses add up. Intensities are computed only from the
+/// time elapsed since the effect started, so the same time always gives the same intensities
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HapticEffect {
+    pulses: Vec<HapticPulse>,
+    length: f32,
+    looping: bool,
+    priority: u8,
+}
+
+impl HapticEffect {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a pulse `start` seconds after the effect starts
+    pub fn pulse(mut self, start: f32, rumble: Rumble, envelope: Envelope) -> Self {
+        let start = start.max(0.0);
+        self.length = self.length.max(start + envelope.duration());
+        self.pulses.push(HapticPulse {
+            start,
+            rumble,
+            envelope,
+        });
+        self
+    }
+
+    /// Add a pulse once everything added before it ended
+    pub fn then(self, rumble: Rumble, envelope: Envelope) -> Self {
+        let start = self.length;
+        self.pulse(start, rumble, envelope)
+    }
+
+    /// Wait `seconds` before the next pulse added with `Self::then`, or before looping
+    pub fn pause(mut self, seconds: f32) -> Self {
+        self.length += seconds.max(0.0);
+        self
+    }
+
+    /// Restart after `Self::duration` instead of stopping
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Effects with a higher priority are mixed first, lower ones only fill the intensity left. 0 by default
+    pub fn priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Two quick beats repeating `bpm` times a minute
+    pub fn heartbeat(intensity: u8, bpm: f32) -> Self {
+        let beat = Envelope::new(0.02, 0.05, 0.08);
+        Self::new()
+            .pulse(0.0, Rumble::new(intensity, 0), beat)
+            .pulse(0.25, Rumble::new(intensity / 2, 0), beat)
+            .pause((60.0 / bpm.max(f32::EPSILON) - beat.duration() - 0.25).max(0.0))
+            .looping(true)
+    }
+
+    /// Low rumble with a light ripple, like an engine running
+    pub fn engine_idle(intensity: u8) -> Self {
+        let ripple = Envelope::new(0.05, 0.0, 0.05);
+        Self::new()
+            .pulse(0.0, Rumble::new(intensity, 0), Envelope::new(0.0, 0.1, 0.0))
+            .pulse(0.0, Rumble::new(0, intensity / 4), ripple)
+            .looping(true)
+    }
+
+    /// Sudden hit fading out
+    pub fn impact(intensity: u8) -> Self {
+        Self::new().pulse(
+            0.0,
+            Rumble::new(intensity, intensity),
+            Envelope::new(0.0, 0.05, 0.3),
+        )
+    }
+
+    /// Short tap on the light motor
+    pub fn click(intensity: u8) -> Self {
+        Self::new().pulse(
+            0.0,
+            Rumble::new(0, intensity),
+            Envelope::new(0.0, 0.03, 0.0),
+        )
+    }
+
+    pub fn pulses(&self) -> &[HapticPulse] {
+        &self.pulses
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Length of the effect in seconds, including pauses
+    pub fn duration(&self) -> f32 {
+        self.length
+    }
+
+    /// Whether a one-shot effect ended `seconds` after it started
+    pub fn is_finished(&self, seconds: f32) -> bool {
+        !self.looping && seconds >= self.length
+    }
+
+    /// Intensities `seconds` after the effect started
+    pub fn rumble_at(&self, seconds: f32) -> Rumble {
+        let [low, high] = self.intensities_at(seconds);
+        Rumble::new(to_byte(low), to_byte(high))
+    }
+
+    /// Low and high motor intensities, in the [0, 1] interval
+    fn intensities_at(&self, seconds: f32) -> [f32; 2] {
+        let seconds = if self.looping && self.length > 0.0 {
+            seconds.max(0.0) % self.length
+        } else {
+            seconds
+        };
+        let mut intensities = [0.0; 2];
+        for pulse in &self.pulses {
+            let level = pulse.envelope.level_at(seconds - pulse.start);
+            intensities[0] += level * pulse.rumble.low as f32 / 255.0;
+            intensities[1] += level * pulse.rumble.high as f32 / 255.0;
+        }
+        intensities.map(|intensity| intensity.min(1.0))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PlayingEffect {
+    id: u32,
+    effect: HapticEffect,
+    started: f32,
+}
+
+/// Mixes the haptic effects playing at the same time. Times are seconds on a timeline chosen by the caller, so the
+/// output can be computed for any point in time. For each motor, effects with the same priority add up, then each
+/// lower priority only fills the intensity the higher ones left: a strong effect drowns weaker background ones
+#[derive(Clone, Debug, Default)]
+pub struct Haptics {
+    effects: Vec<PlayingEffect>,
+    next_id: u32,
+}
+
+impl Haptics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start an effect at `now`, returns an id to stop it
+    pub fn play(&mut self, effect: HapticEffect, now: f32) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.effects.push(PlayingEffect {
+            id,
+            effect,
+            started: now,
+        });
+        id
+    }
+
+    pub fn stop(&mut self, id: u32) {
+        self.effects.retain(|playing| playing.id != id);
+    }
+
+    pub fn stop_all(&mut self) {
+        self.effects.clear();
+    }
+
+    /// Whether the effect was not stopped and, for one-shot effects, had not ended at the last `Self::update`
+    pub fn is_playing(&self, id: u32) -> bool {
+        self.effects.iter().any(|playing| playing.id == id)
+    }
+
+    /// Forget the one-shot effects that ended at `now`
+    pub fn update(&mut self, now: f32) {
+        self.effects
+            .retain(|playing| !playing.effect.is_finished(now - playing.started));
+    }
+
+    /// Mixed intensities at `now`, with `base` below every effect
+    pub fn output(&self, now: f32, base: Rumble) -> Rumble {
+        let mut effects = self.effects.iter().collect::<Vec<_>>();
+        effects.sort_by_key(|playing| Reverse(playing.effect.priority));
+
+        let mut mixed = [0.0_f32; 2];
+        for group in effects.chunk_by(|a, b| a.effect.priority == b.effect.priority) {
+            let mut sum = [0.0_f32; 2];
+            for playing in group {
+                let intensities = playing.effect.intensities_at(now - playing.started);
+                sum[0] += intensities[0];
+                sum[1] += intensities[1];
+            }
+            Self::fill(&mut mixed, sum);
+        }
+        Self::fill(
+            &mut mixed,
+            [base.low as f32 / 255.0, base.high as f32 / 255.0],
+        );
+        Rumble::new(to_byte(mixed[0]), to_byte(mixed[1]))
+    }
+
+    fn fill(mixed: &mut [f32; 2], intensities: [f32; 2]) {
+        for (mixed, intensity) in mixed.iter_mut().zip(intensities) {
+            *mixed += intensity.min(1.0) * (1.0 - *mixed);
+        }
+    }
+}
+
+/// Effect playing on the rumble motors, see `DualSense::play_haptic`
+#[derive(Clone, Debug)]
+pub struct HapticHandle {
+    id: u32,
+    haptics: Arc<Mutex<Haptics>>,
+}
+
+impl HapticHandle {
+    pub(crate) fn new(id: u32, haptics: Arc<Mutex<Haptics>>) -> Self {
+        Self { id, haptics }
+    }
+
+    pub fn stop(&self) {
+        self.haptics.lock().unwrap().stop(self.id);
+    }
+
+    /// Whether the effect is still playing: it was not stopped or, for one-shot effects, had not ended
+    pub fn is_playing(&self) -> bool {
+        self.haptics.lock().unwrap().is_playing(self.id)
+    }
+}
+
+fn to_byte(intensity: f32) -> u8 {
+    (intensity.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Constant intensity for `seconds`
+    fn hold(seconds: f32) -> Envelope {
+        Envelope::new(0.0, seconds, 0.0)
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn envelope_levels() {
+        let envelope = Envelope::new(0.1, 0.2, 0.1);
+        assert_close(envelope.duration(), 0.4);
+        assert_close(envelope.level_at(-0.1), 0.0);
+        assert_close(envelope.level_at(0.0), 0.0);
+        assert_close(envelope.level_at(0.05), 0.5);
+        assert_close(envelope.level_at(0.1), 1.0);
+        assert_close(envelope.level_at(0.25), 1.0);
+        assert_close(envelope.level_at(0.35), 0.5);
+        assert_close(envelope.level_at(0.4), 0.0);
+        assert_close(hold(0.1).level_at(0.0), 1.0);
+    }
+
+    #[test]
+    fn attack_sustain_decay_over_time() {
+        let mut haptics = Haptics::new();
+        haptics.play(
+            HapticEffect::new().pulse(0.0, Rumble::new(200, 100), Envelope::new(0.1, 0.2, 0.1)),
+            10.0,
+        );
+        let frames = [
+            (10.0, Rumble::OFF),
+            (10.05, Rumble::new(100, 50)),
+            (10.2, Rumble::new(200, 100)),
+            (10.35, Rumble::new(100, 50)),
+            (10.5, Rumble::OFF),
+        ];
+        for (now, expected) in frames {
+            assert_eq!(haptics.output(now, Rumble::OFF), expected, "at {now}");
+        }
+    }
+
+    #[test]
+    fn one_shot_effects_end() {
+        let mut haptics = Haptics::new();
+        let id = haptics.play(HapticEffect::click(255), 1.0);
+        haptics.update(1.02);
+        assert!(haptics.is_playing(id));
+        haptics.update(1.05);
+        assert!(!haptics.is_playing(id));
+        assert_eq!(haptics.output(1.02, Rumble::OFF), Rumble::OFF);
+    }
+
+    #[test]
+    fn priority_mix() {
+        let strong = HapticEffect::new()
+            .pulse(0.0, Rumble::new(153, 0), hold(1.0))
+            .priority(1);
+        let background = HapticEffect::new().pulse(0.0, Rumble::new(153, 51), hold(1.0));
+
+        let mut haptics = Haptics::new();
+        let strong_id = haptics.play(strong, 0.0);
+        // higher priority first: 0.6, then the background fills 60% of the remaining 0.4
+        assert_eq!(haptics.output(0.5, Rumble::OFF), Rumble::new(153, 0));
+        haptics.play(background.clone(), 0.0);
+        assert_eq!(haptics.output(0.5, Rumble::OFF), Rumble::new(214, 51));
+        // the base rumble is below every effect
+        assert_eq!(
+            haptics.output(0.5, Rumble::new(255, 255)),
+            Rumble::new(255, 255)
+        );
+        haptics.stop(strong_id);
+        assert_eq!(
+            haptics.output(0.5, Rumble::new(102, 0)),
+            Rumble::new(194, 51)
+        );
+
+        // effects with the same priority add up
+        haptics.play(background, 0.0);
+        assert_eq!(haptics.output(0.5, Rumble::OFF), Rumble::new(255, 102));
+        haptics.stop_all();
+        assert_eq!(haptics.output(0.5, Rumble::OFF), Rumble::OFF);
+    }
+
+    #[test]
+    fn saturation() {
+        let effect = HapticEffect::new()
+            .pulse(0.0, Rumble::new(200, 200), hold(1.0))
+            .pulse(0.5, Rumble::new(200, 10), hold(1.0));
+        assert_eq!(effect.rumble_at(0.25), Rumble::new(200, 200));
+        assert_eq!(effect.rumble_at(0.75), Rumble::new(255, 210));
+
+        let mut haptics = Haptics::new();
+        haptics.play(effect.clone(), 0.0);
+        haptics.play(effect, 0.0);
+        assert_eq!(haptics.output(0.75, Rumble::OFF), Rumble::new(255, 255));
+    }
+
+    #[test]
+    fn looping_wraps() {
+        let effect = HapticEffect::new()
+            .pulse(0.0, Rumble::new(255, 0), hold(0.1))
+            .pause(0.1)
+            .looping(true);
+        assert_close(effect.duration(), 0.2);
+        let mut haptics = Haptics::new();
+        let id = haptics.play(effect, 2.0);
+        let frames = [
+            (2.05, Rumble::new(255, 0)),
+            (2.15, Rumble::OFF),
+            (2.25, Rumble::new(255, 0)),
+            (2.35, Rumble::OFF),
+            (12.05, Rumble::new(255, 0)),
+        ];
+        for (now, expected) in frames {
+            haptics.update(now);
+            assert_eq!(haptics.output(now, Rumble::OFF), expected, "at {now}");
+        }
+        assert!(haptics.is_playing(id));
+    }
+
+    #[test]
+    fn heartbeat() {
+        let effect = HapticEffect::heartbeat(200, 60.0);
+        assert_close(effect.duration(), 1.0);
+        assert_eq!(effect.rumble_at(0.05), Rumble::new(200, 0));
+        assert_eq!(effect.rumble_at(0.3), Rumble::new(100, 0));
+        assert_eq!(effect.rumble_at(0.7), Rumble::OFF);
+        assert_eq!(effect.rumble_at(1.05), Rumble::new(200, 0));
+    }
+}
@@ -0,0 +1,279 @@
+use std::time::Instant;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::animation::LightbarAnimation;
+
+/// Color of the lightbar
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Color {
+    pub const BLACK: Self = Self::new(0, 0, 0);
+    pub const WHITE: Self = Self::new(255, 255, 255);
+    pub const RED: Self = Self::new(255, 0, 0);
+    pub const GREEN: Self = Self::new(0, 255, 0);
+    pub const BLUE: Self = Self::new(0, 0, 255);
+    pub const YELLOW: Self = Self::new(255, 255, 0);
+    pub const CYAN: Self = Self::new(0, 255, 255);
+    pub const MAGENTA: Self = Self::new(255, 0, 255);
+
+    pub const fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// `hue` in degrees, `saturation` and `value` in the [0, 1] interval
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let (saturation, value) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (red, green, blue) = match hue as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let offset = value - chroma;
+        Self::new(
+            Self::to_channel(red + offset),
+            Self::to_channel(green + offset),
+            Self::to_channel(blue + offset),
+        )
+    }
+
+    /// Hue in degrees, saturation and value in the [0, 1] interval
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (red, green, blue) = (
+            self.red as f32 / 255.0,
+            self.green as f32 / 255.0,
+            self.blue as f32 / 255.0,
+        );
+        let max = red.max(green).max(blue);
+        let chroma = max - red.min(green).min(blue);
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == red {
+            60.0 * ((green - blue) / chroma).rem_euclid(6.0)
+        } else if max == green {
+            60.0 * ((blue - red) / chroma + 2.0)
+        } else {
+            60.0 * ((red - green) / chroma + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        (hue, saturation, max)
+    }
+
+    /// Parse `RRGGBB` or `RGB` hex digits, optionally starting with `#`
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        // `from_str_radix` would also accept a sign
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        match hex.len() {
+            6 => Some(Self::new(
+                u8::from_str_radix(&hex[0..2], 16).ok()?,
+                u8::from_str_radix(&hex[2..4], 16).ok()?,
+                u8::from_str_radix(&hex[4..6], 16).ok()?,
+            )),
+            3 => {
+                let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).map(|d| d * 17);
+                Some(Self::new(digit(0).ok()?, digit(1).ok()?, digit(2).ok()?))
+            }
+            _ => None,
+        }
+    }
+
+    /// `RRGGBB` hex digits starting with `#`
+    pub fn to_hex(self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.red, self.green, self.blue)
+    }
+
+    /// Multiply every channel by `factor`, in the [0, 1] interval
+    pub fn scale(&self, factor: f32) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+        Self::new(
+            Self::to_channel(self.red as f32 / 255.0 * factor),
+            Self::to_channel(self.green as f32 / 255.0 * factor),
+            Self::to_channel(self.blue as f32 / 255.0 * factor),
+        )
+    }
+
+    /// Blend towards `to`, `t` being in the [0, 1] interval
+    pub fn lerp(self, to: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let channel =
+            |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+        Self::new(
+            channel(self.red, to.red),
+            channel(self.green, to.green),
+            channel(self.blue, to.blue),
+        )
+    }
+
+    fn to_channel(value: f32) -> u8 {
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    fn from(value: (u8, u8, u8)) -> Self {
+        Self::new(value.0, value.1, value.2)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PlayingAnimation {
+    id: u32,
+    animation: LightbarAnimation,
+    started: Instant,
+}
+
+/// Color set by the user, the brightness it is sent with and the animation playing over it
+#[derive(Clone, Debug)]
+pub(crate) struct Lightbar {
+    pub(crate) color: Color,
+    pub(crate) brightness: f32,
+    animation: Option<PlayingAnimation>,
+    next_id: u32,
+    changed: bool,
+}
+
+impl Lightbar {
+    /// Color written to the output report
+    pub(crate) fn output(&self, now: Instant) -> Color {
+        let color = match &self.animation {
+            Some(playing) => playing
+                .animation
+                .color_at(now.duration_since(playing.started).as_secs_f32()),
+            None => self.color,
+        };
+        color.scale(self.brightness)
+    }
+
+    /// Replace the playing animation, returns the id of the new one
+    pub(crate) fn play(&mut self, animation: LightbarAnimation, now: Instant) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.animation = Some(PlayingAnimation {
+            id,
+            animation,
+            started: now,
+        });
+        id
+    }
+
+    /// Stop the animation with the given id, or any animation for `None`
+    pub(crate) fn stop(&mut self, id: Option<u32>) {
+        if self
+            .animation
+            .as_ref()
+            .is_some_and(|playing| id.is_none_or(|id| id == playing.id))
+        {
+            self.animation = None;
+            self.changed = true;
+        }
+    }
+
+    pub(crate) fn is_playing(&self, id: u32) -> bool {
+        self.animation
+            .as_ref()
+            .is_some_and(|playing| playing.id == id)
+    }
+
+    /// Advance the animation, returns the color to send if it may have changed since the previous call. A one-shot
+    /// animation leaves the lightbar on its last color
+    pub(crate) fn tick(&mut self, now: Instant) -> Option<Color> {
+        if let Some(playing) = &self.animation {
+            let elapsed = now.duration_since(playing.started).as_secs_f32();
+            if playing.animation.is_finished(elapsed) {
+                self.color = playing.animation.color_at(elapsed);
+                self.animation = None;
+            }
+        } else if !self.changed {
+            return None;
+        }
+        self.changed = false;
+        Some(self.output(now))
+    }
+}
+
+impl Default for Lightbar {
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK,
+            brightness: 1.0,
+            animation: None,
+            next_id: 0,
+            changed: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex() {
+        assert_eq!(Color::from_hex("#FF8000"), Some(Color::new(255, 128, 0)));
+        assert_eq!(Color::from_hex("00ff7f"), Some(Color::new(0, 255, 127)));
+        assert_eq!(Color::from_hex("#f80"), Some(Color::new(255, 136, 0)));
+        assert_eq!(Color::new(1, 171, 255).to_hex(), "#01ABFF");
+    }
+
+    #[test]
+    fn hex_errors() {
+        for hex in ["", "#", "#F", "#FF", "#FFFF", "#FFFFF", "#FFFFFFF", "##FFF"] {
+            assert_eq!(Color::from_hex(hex), None, "{hex}");
+        }
+        for hex in ["#GG0000", "12345Z", "#+12345", "-1F", " FFFFF", "#ÿÿÿ"] {
+            assert_eq!(Color::from_hex(hex), None, "{hex}");
+        }
+    }
+
+    #[test]
+    fn hsv_hue_boundaries() {
+        let hues = [
+            (0.0, Color::RED),
+            (60.0, Color::YELLOW),
+            (120.0, Color::GREEN),
+            (180.0, Color::CYAN),
+            (240.0, Color::BLUE),
+            (300.0, Color::MAGENTA),
+            (360.0, Color::RED),
+            (720.0, Color::RED),
+            (-60.0, Color::MAGENTA),
+            (-0.0001, Color::RED),
+            (359.9, Color::new(255, 0, 0)),
+            (30.0, Color::new(255, 128, 0)),
+        ];
+        for (hue, color) in hues {
+            assert_eq!(Color::from_hsv(hue, 1.0, 1.0), color, "{hue}");
+        }
+        assert_eq!(Color::from_hsv(200.0, 0.0, 0.5), Color::new(128, 128, 128));
+        assert_eq!(Color::from_hsv(200.0, 1.0, 0.0), Color::BLACK);
+    }
+
+    #[test]
+    fn to_hsv() {
+        assert_eq!(Color::RED.to_hsv(), (0.0, 1.0, 1.0));
+        assert_eq!(Color::YELLOW.to_hsv(), (60.0, 1.0, 1.0));
+        assert_eq!(Color::CYAN.to_hsv(), (180.0, 1.0, 1.0));
+        assert_eq!(Color::MAGENTA.to_hsv(), (300.0, 1.0, 1.0));
+        assert_eq!(Color::WHITE.to_hsv(), (0.0, 0.0, 1.0));
+        assert_eq!(Color::BLACK.to_hsv(), (0.0, 0.0, 0.0));
+        // just before red wraps around
+        let (hue, _, _) = Color::new(255, 0, 1).to_hsv();
+        assert!(hue > 359.0 && hue < 360.0, "{hue}");
+    }
+}
@@ -1,10 +1,33 @@
+pub mod analog_pad;
+pub mod animation;
+pub mod calibration;
+pub mod combo_builder;
+pub mod curve;
+pub mod dead_zone;
+pub mod direction;
 pub mod dpad;
+pub mod fusion;
+pub mod gesture;
+pub mod gyro_aim;
+pub mod gyro_bias;
+pub mod haptics;
+pub mod lightbar;
+pub mod motion;
+pub mod mute;
 pub(crate) mod offset;
+pub mod output_batch;
+pub mod player_leds;
 pub mod property;
+pub mod report;
+pub mod rumble;
 pub mod symbols;
-pub mod trigger_effect;
-pub(crate) mod valuetype;
-pub mod analog_pad;
+pub mod touch;
+pub mod touch_gesture;
+pub mod touch_zone;
+pub mod trackpad;
 pub mod traits;
 pub mod trigger;
-pub mod combo_builder;
+pub mod trigger_effect;
+pub mod trigger_feedback;
+pub mod trigger_preset;
+pub(crate) mod valuetype;
@@ -0,0 +1,333 @@
+use std::hash::{Hash, Hasher};
+
+use super::{gyro_bias::GyroBias, property::ComboProperty};
+
+/// Id of the feature report holding the IMU calibration
+pub(crate) const IMU_CALIBRATION_REPORT_ID: u8 = 0x05;
+/// Size of the IMU calibration feature report, including the report id
+pub(crate) const IMU_CALIBRATION_REPORT_SIZE: usize = 41;
+/// Standard gravity, used to convert accelerations from g to m/s²
+pub const STANDARD_GRAVITY: f32 = 9.80665;
+/// Sensor timestamp ticks per second
+pub(crate) const TIMESTAMP_TICKS_PER_SECOND: f32 = 3_000_000.0;
+
+/// Conversion from raw sensor values to physical units for a single axis: `(raw - bias) * numerator / denominator`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AxisCalibration {
+    pub bias: i16,
+    pub numerator: i32,
+    pub denominator: i32,
+}
+
+impl AxisCalibration {
+    pub fn apply(&self, raw: i16) -> f32 {
+        (raw as i32 - self.bias as i32) as f32 * self.numerator as f32 / self.denominator as f32
+    }
+
+    /// Difference of raw values matching `value` in physical units, the reverse of `Self::apply` without the
+    /// bias
+    pub fn raw_offset(&self, value: f32) -> i16 {
+        (value * self.denominator as f32 / self.numerator as f32)
+            .round()
+            .clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    }
+}
+
+/// Bias and sensitivity of the gyroscope (degrees/second) and accelerometer (g) axes, as stored in the
+/// controller's IMU calibration feature report
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ImuCalibration {
+    pub gyro: [AxisCalibration; 3],
+    pub accel: [AxisCalibration; 3],
+}
+
+impl ImuCalibration {
+    /// Parse the IMU calibration feature report, including the report id. Returns `None` if the report is
+    /// malformed
+    pub fn from_feature_report(data: &[u8]) -> Option<Self> {
+        if data.len() < IMU_CALIBRATION_REPORT_SIZE || data[0] != IMU_CALIBRATION_REPORT_ID {
+            return None;
+        }
+        let read = |byte: usize| i16::from_le_bytes([data[byte], data[byte + 1]]) as i32;
+
+        let speed_2x = read(19) + read(21);
+        let mut gyro = [AxisCalibration {
+            bias: 0,
+            numerator: 0,
+            denominator: 0,
+        }; 3];
+        for (axis, calibration) in gyro.iter_mut().enumerate() {
+            let plus = read(7 + axis * 4);
+            let minus = read(9 + axis * 4);
+            *calibration = AxisCalibration {
+                bias: read(1 + axis * 2) as i16,
+                numerator: speed_2x,
+                denominator: plus - minus,
+            };
+        }
+
+        let mut accel = gyro;
+        for (axis, calibration) in accel.iter_mut().enumerate() {
+            let plus = read(23 + axis * 4);
+            let minus = read(25 + axis * 4);
+            let range_2g = plus - minus;
+            *calibration = AxisCalibration {
+                bias: (plus - range_2g / 2) as i16,
+                numerator: 2,
+                denominator: range_2g,
+            };
+        }
+
+        let valid = gyro
+            .iter()
+            .chain(accel.iter())
+            .all(|axis| axis.denominator != 0 && axis.numerator != 0);
+        valid.then_some(Self { gyro, accel })
+    }
+}
+
+impl Default for ImuCalibration {
+    /// Nominal sensitivity, used when the controller's calibration can't be read: ±2000 degrees/second for the
+    /// gyroscope and 8192 units per g for the accelerometer
+    fn default() -> Self {
+        let gyro = AxisCalibration {
+            bias: 0,
+            numerator: 2000,
+            denominator: i16::MAX as i32,
+        };
+        let accel = AxisCalibration {
+            bias: 0,
+            numerator: 1,
+            denominator: 8192,
+        };
+        Self {
+            gyro: [gyro; 3],
+            accel: [accel; 3],
+        }
+    }
+}
+
+/// Reading of all six motion axes, taken at the same time. Two samples are equal when their raw values are equal,
+/// regardless of the calibration and gyroscope bias they were configured with
+#[derive(Clone, Copy, Debug)]
+pub struct MotionSample {
+    gyro: [i16; 3],
+    accel: [i16; 3],
+    timestamp: u32,
+    sequence: u8,
+    calibration: ImuCalibration,
+    bias: GyroBias,
+}
+
+impl MotionSample {
+    /// Decode the gyroscope, accelerometer and sensor timestamp bytes of the input report
+    pub(crate) fn new(data: &[u8]) -> Self {
+        let read = |byte: usize| i16::from_le_bytes([data[byte], data[byte + 1]]);
+        Self {
+            gyro: [read(0), read(2), read(4)],
+            accel: [read(6), read(8), read(10)],
+            timestamp: u32::from_le_bytes([data[12], data[13], data[14], data[15]]),
+            sequence: 0,
+            calibration: ImuCalibration::default(),
+            bias: GyroBias::default(),
+        }
+    }
+
+    pub(crate) fn with_calibration(mut self, calibration: ImuCalibration) -> Self {
+        self.calibration = calibration;
+        self
+    }
+
+    pub(crate) fn with_sequence(mut self, sequence: u8) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    pub(crate) fn with_bias(mut self, bias: GyroBias) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Angular velocity around the X (pitch), Y (yaw) and Z (roll) axes, in degrees/second, with the gyroscope
+    /// bias removed
+    pub fn gyro(&self) -> [f32; 3] {
+        self.bias.apply(self.gyro_uncorrected())
+    }
+
+    /// Angular velocity in degrees/second, without the gyroscope bias removed
+    pub fn gyro_uncorrected(&self) -> [f32; 3] {
+        [0, 1, 2].map(|axis| self.calibration.gyro[axis].apply(self.gyro[axis]))
+    }
+
+    /// Acceleration along the X, Y and Z axes, in g
+    pub fn accel(&self) -> [f32; 3] {
+        [0, 1, 2].map(|axis| self.calibration.accel[axis].apply(self.accel[axis]))
+    }
+
+    /// Acceleration along the X, Y and Z axes, in m/s²
+    pub fn accel_ms2(&self) -> [f32; 3] {
+        self.accel().map(|value| value * STANDARD_GRAVITY)
+    }
+
+    pub fn raw_gyro(&self) -> [i16; 3] {
+        self.gyro
+    }
+
+    pub fn raw_accel(&self) -> [i16; 3] {
+        self.accel
+    }
+
+    /// Sensor timestamp, counting in units of 1/3 microseconds and wrapping around
+    pub fn timestamp(&self) -> u32 {
+        self.timestamp
+    }
+
+    /// Sensor timestamp in microseconds, wrapping around
+    pub fn timestamp_micros(&self) -> u32 {
+        self.timestamp / 3
+    }
+
+    /// Sequence counter of the report the sample was received in, see `ReportStats::sequence`
+    pub fn sequence(&self) -> u8 {
+        self.sequence
+    }
+
+    /// Seconds elapsed since an earlier sensor timestamp, accounting for the wrap around
+    pub fn elapsed_seconds(&self, earlier_timestamp: u32) -> f32 {
+        self.timestamp.wrapping_sub(earlier_timestamp) as f32 / TIMESTAMP_TICKS_PER_SECOND
+    }
+
+    pub fn calibration(&self) -> ImuCalibration {
+        self.calibration
+    }
+
+    /// Bias removed by `Self::gyro`
+    pub fn bias(&self) -> GyroBias {
+        self.bias
+    }
+}
+
+impl Default for MotionSample {
+    fn default() -> Self {
+        Self::new(&[0; 16])
+    }
+}
+
+impl PartialEq for MotionSample {
+    fn eq(&self, other: &Self) -> bool {
+        self.gyro == other.gyro && self.accel == other.accel && self.timestamp == other.timestamp
+    }
+}
+
+impl Eq for MotionSample {}
+
+impl Hash for MotionSample {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.gyro.hash(state);
+        self.accel.hash(state);
+        self.timestamp.hash(state);
+    }
+}
+
+impl From<ComboProperty> for MotionSample {
+    fn from(value: ComboProperty) -> Self {
+        match value {
+            ComboProperty::Motion(v) => v,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GYRO_BIAS: [i16; 3] = [2, -3, 5];
+    const GYRO_PLUS: [i16; 3] = [8870, 8900, 8880];
+    const GYRO_MINUS: [i16; 3] = [-8870, -8850, -8890];
+    const SPEED: [i16; 2] = [540, 540];
+    const ACCEL_PLUS: [i16; 3] = [8200, 8150, 8260];
+    const ACCEL_MINUS: [i16; 3] = [-8180, -8230, -8120];
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{actual} != {expected}");
+    }
+
+    /// Calibration feature report laid out as read by the Linux hid-playstation driver
+    fn report() -> [u8; IMU_CALIBRATION_REPORT_SIZE] {
+        let mut data = [0; IMU_CALIBRATION_REPORT_SIZE];
+        data[0] = IMU_CALIBRATION_REPORT_ID;
+        let mut write = |byte: usize, value: i16| {
+            data[byte..byte + 2].copy_from_slice(&value.to_le_bytes());
+        };
+        for axis in 0..3 {
+            write(1 + axis * 2, GYRO_BIAS[axis]);
+            write(7 + axis * 4, GYRO_PLUS[axis]);
+            write(9 + axis * 4, GYRO_MINUS[axis]);
+            write(23 + axis * 4, ACCEL_PLUS[axis]);
+            write(25 + axis * 4, ACCEL_MINUS[axis]);
+        }
+        write(19, SPEED[0]);
+        write(21, SPEED[1]);
+        data
+    }
+
+    #[test]
+    fn feature_report_matches_the_kernel() {
+        let calibration = ImuCalibration::from_feature_report(&report()).unwrap();
+        let speed_2x = (SPEED[0] + SPEED[1]) as f32;
+        for axis in 0..3 {
+            // the kernel scales by speed_2x / (plus - minus), in units of 1/1024 degrees/second
+            let range = GYRO_PLUS[axis] as f32 - GYRO_MINUS[axis] as f32;
+            for raw in [0, 1000, -2500, i16::MAX] {
+                let expected = (raw as f32 - GYRO_BIAS[axis] as f32) * speed_2x / range;
+                assert_close(calibration.gyro[axis].apply(raw), expected);
+            }
+            // and by 2 / (plus - minus) around the middle of the range for the accelerometer, in units of 1/8192 g
+            let range_2g = ACCEL_PLUS[axis] as i32 - ACCEL_MINUS[axis] as i32;
+            let bias = ACCEL_PLUS[axis] as i32 - range_2g / 2;
+            assert_eq!(calibration.accel[axis].bias as i32, bias);
+            assert_close(calibration.accel[axis].apply(ACCEL_PLUS[axis]), 1.0);
+            assert_close(calibration.accel[axis].apply(ACCEL_MINUS[axis]), -1.0);
+            let expected = (4000 - bias) as f32 * 2.0 / range_2g as f32;
+            assert_close(calibration.accel[axis].apply(4000), expected);
+        }
+    }
+
+    #[test]
+    fn short_or_foreign_report() {
+        let data = report();
+        assert_eq!(ImuCalibration::from_feature_report(&data[..40]), None);
+        assert_eq!(ImuCalibration::from_feature_report(&[]), None);
+        let mut foreign = data;
+        foreign[0] = 0x09;
+        assert_eq!(ImuCalibration::from_feature_report(&foreign), None);
+        // longer buffers are accepted
+        let mut long = [0; 64];
+        long[..data.len()].copy_from_slice(&data);
+        assert!(ImuCalibration::from_feature_report(&long).is_some());
+    }
+
+    #[test]
+    fn empty_report_falls_back_to_nominal() {
+        let mut data = [0; IMU_CALIBRATION_REPORT_SIZE];
+        data[0] = IMU_CALIBRATION_REPORT_ID;
+        let calibration = ImuCalibration::from_feature_report(&data).unwrap_or_default();
+        assert_eq!(calibration, ImuCalibration::default());
+
+        let calibration = ImuCalibration::default();
+        assert_close(calibration.gyro[0].apply(i16::MAX), 2000.0);
+        assert_close(calibration.accel[2].apply(8192), 1.0);
+    }
+
+    #[test]
+    fn raw_offset_reverses_apply() {
+        let calibration = ImuCalibration::from_feature_report(&report()).unwrap();
+        let axis = calibration.gyro[1];
+        let offset = axis.raw_offset(12.5);
+        // within half a raw unit
+        let unit = axis.numerator as f32 / axis.denominator as f32;
+        assert!((axis.apply(offset + axis.bias) - 12.5).abs() <= unit / 2.0);
+        assert_eq!(axis.raw_offset(1e9), i16::MAX);
+    }
+}
@@ -0,0 +1,184 @@
+/// State of the LED on the mute button
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MuteLed {
+    #[default]
+    Off,
+    On,
+    Pulsing,
+}
+
+impl MuteLed {
+    pub(crate) fn byte(self) -> u8 {
+        match self {
+            MuteLed::Off => 0x00,
+            MuteLed::On => 0x01,
+            MuteLed::Pulsing => 0x02,
+        }
+    }
+
+    pub(crate) fn from_byte(value: u8) -> Self {
+        match value {
+            0x01 => MuteLed::On,
+            0x02 => MuteLed::Pulsing,
+            _ => MuteLed::Off,
+        }
+    }
+}
+
+/// Features the controller turns off or mutes, sent as flags in a single byte. Nothing is turned off by default
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PowerSave {
+    pub disable_touch: bool,
+    pub disable_motion: bool,
+    pub disable_haptics: bool,
+    pub disable_audio: bool,
+    pub mute_microphone: bool,
+    pub mute_speaker: bool,
+    pub mute_headphone: bool,
+    pub mute_haptics: bool,
+}
+
+impl PowerSave {
+    pub(crate) fn byte(self) -> u8 {
+        [
+            self.disable_touch,
+            self.disable_motion,
+            self.disable_haptics,
+            self.disable_audio,
+            self.mute_microphone,
+            self.mute_speaker,
+            self.mute_headphone,
+            self.mute_haptics,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |byte, (bit, set)| byte | ((*set as u8) << bit))
+    }
+
+    pub(crate) fn from_byte(value: u8) -> Self {
+        let bit = |i: u8| value & (1 << i) != 0;
+        Self {
+            disable_touch: bit(0),
+            disable_motion: bit(1),
+            disable_haptics: bit(2),
+            disable_audio: bit(3),
+            mute_microphone: bit(4),
+            mute_speaker: bit(5),
+            mute_headphone: bit(6),
+            mute_haptics: bit(7),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::{output_batch::OutputBatch, property::OutputProperty};
+
+    /// Index and value of the output report bytes written by the batch
+    fn report_bytes(batch: &OutputBatch) -> Vec<(usize, u8)> {
+        batch
+            .changes
+            .iter()
+            .map(|change| (change.property().byte(), change.apply(0)))
+            .collect()
+    }
+
+    #[test]
+    fn mute_led_byte() {
+        assert_eq!(OutputProperty::Mute.byte(), 9);
+        for (state, byte) in [
+            (MuteLed::Off, 0x00),
+            (MuteLed::On, 0x01),
+            (MuteLed::Pulsing, 0x02),
+        ] {
+            assert_eq!(
+                report_bytes(OutputBatch::default().set_mute_led(state)),
+                [(9, byte)]
+            );
+            assert_eq!(MuteLed::from_byte(byte), state);
+        }
+        assert_eq!(MuteLed::from_byte(0x03), MuteLed::Off);
+    }
+
+    #[test]
+    fn power_save_byte() {
+        assert_eq!(OutputProperty::PowerSave.byte(), 10);
+        let none = PowerSave::default();
+        let flags = [
+            (
+                PowerSave {
+                    disable_touch: true,
+                    ..none
+                },
+                0x01,
+            ),
+            (
+                PowerSave {
+                    disable_motion: true,
+                    ..none
+                },
+                0x02,
+            ),
+            (
+                PowerSave {
+                    disable_haptics: true,
+                    ..none
+                },
+                0x04,
+            ),
+            (
+                PowerSave {
+                    disable_audio: true,
+                    ..none
+                },
+                0x08,
+            ),
+            (
+                PowerSave {
+                    mute_microphone: true,
+                    ..none
+                },
+                0x10,
+            ),
+            (
+                PowerSave {
+                    mute_speaker: true,
+                    ..none
+                },
+                0x20,
+            ),
+            (
+                PowerSave {
+                    mute_headphone: true,
+                    ..none
+                },
+                0x40,
+            ),
+            (
+                PowerSave {
+                    mute_haptics: true,
+                    ..none
+                },
+                0x80,
+            ),
+        ];
+        for (power_save, byte) in flags {
+            assert_eq!(
+                report_bytes(OutputBatch::default().set_power_save(power_save)),
+                [(10, byte)]
+            );
+            assert_eq!(PowerSave::from_byte(byte), power_save);
+        }
+        assert_eq!(PowerSave::default().byte(), 0x00);
+        let power_save = PowerSave {
+            disable_motion: true,
+            mute_microphone: true,
+            mute_haptics: true,
+            ..Default::default()
+        };
+        assert_eq!(power_save.byte(), 0x92);
+        assert_eq!(PowerSave::from_byte(0x92), power_save);
+        assert_eq!(PowerSave::from_byte(0xFF).byte(), 0xFF);
+    }
+}
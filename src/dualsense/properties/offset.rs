@@ -35,11 +35,11 @@ impl Offset {
             bits: bit..bit + 1,
         }
     }
-    
+
     pub(crate) fn is_whole_byte(&self) -> bool {
         self.bits == (0..8)
     }
-    
+
     pub(crate) fn is_single_byte(&self) -> bool {
         self.bytes.clone().count() == 1
     }
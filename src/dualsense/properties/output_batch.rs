@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use super::{
+    lightbar::Color,
+    mute::{MuteLed, PowerSave},
+    player_leds::{LedBrightness, PlayerLeds},
+    property::OutputProperty,
+    rumble::Rumble,
+    trigger_effect::{TriggerEffect, TriggerEffectError},
+};
+
+/// Bit of the player LEDs byte turning the LEDs on without fading in
+const PLAYER_LEDS_INSTANT: u8 = 0x20;
+/// Bits of the player LEDs byte lighting each LED
+const PLAYER_LEDS_MASK: u8 = 0x1F;
+/// Bit of the third valid flags byte allowing the player LEDs brightness to change
+const VALID_FLAG2_LED_BRIGHTNESS: u8 = 0x01;
+/// Bit of the third valid flags byte enabling the improved rumble emulation
+const VALID_FLAG2_IMPROVED_RUMBLE: u8 = 0x04;
+
+/// Change of an output byte
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum OutputChange {
+    Set(OutputProperty, u8),
+    /// Replace the bits under `mask` with the ones of `value`
+    Bits {
+        property: OutputProperty,
+        mask: u8,
+        value: u8,
+    },
+}
+
+impl OutputChange {
+    pub(crate) fn apply(&self, current: u8) -> u8 {
+        match *self {
+            OutputChange::Set(_, value) => value,
+            OutputChange::Bits { mask, value, .. } => (current & !mask) | (value & mask),
+        }
+    }
+
+    pub(crate) fn property(&self) -> OutputProperty {
+        match *self {
+            OutputChange::Set(property, _) | OutputChange::Bits { property, .. } => property,
+        }
+    }
+}
+
+/// Output changes collected by `DualSense::update` and sent together in the same report
+#[derive(Clone, Debug, Default)]
+pub struct OutputBatch {
+    pub(crate) changes: Vec<OutputChange>,
+    pub(crate) lightbar: Option<Color>,
+    pub(crate) lightbar_brightness: Option<f32>,
+    pub(crate) rumble: Option<Rumble>,
+    pub(crate) error: Option<TriggerEffectError>,
+}
+
+impl OutputBatch {
+    /// Set the lightbar color and stop the playing animation
+    pub fn set_lightbar(&mut self, color: Color) -> &mut Self {
+        self.lightbar = Some(color);
+        self
+    }
+
+    /// Scale the lightbar color by `brightness`, in the [0, 1] interval
+    pub fn set_lightbar_brightness(&mut self, brightness: f32) -> &mut Self {
+        self.lightbar_brightness = Some(brightness.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Set both motor intensities and stop the playing pattern
+    pub fn set_rumble(&mut self, low_freq: u8, high_freq: u8) -> &mut Self {
+        self.rumble = Some(Rumble::new(low_freq, high_freq));
+        self
+    }
+
+    pub fn set_improved_rumble(&mut self, enabled: bool) -> &mut Self {
+        self.bits(
+            OutputProperty::ValidFlag2,
+            VALID_FLAG2_IMPROVED_RUMBLE,
+            enabled,
+        )
+    }
+
+    /// The batch is not sent if the effect is invalid
+    pub fn set_left_trigger_effect(&mut self, effect: TriggerEffect) -> &mut Self {
+        self.trigger_effect(&OutputProperty::LEFT_EFFECT, effect)
+    }
+
+    /// The batch is not sent if the effect is invalid
+    pub fn set_right_trigger_effect(&mut self, effect: TriggerEffect) -> &mut Self {
+        self.trigger_effect(&OutputProperty::RIGHT_EFFECT, effect)
+    }
+
+    pub fn set_player_leds(&mut self, leds: PlayerLeds) -> &mut Self {
+        self.changes.push(OutputChange::Bits {
+            property: OutputProperty::PlayerLight,
+            mask: PLAYER_LEDS_MASK,
+            value: leds.mask(),
+        });
+        self
+    }
+
+    pub fn set_player_leds_brightness(&mut self, brightness: LedBrightness) -> &mut Self {
+        self.changes.push(OutputChange::Set(
+            OutputProperty::LedBrightness,
+            brightness.byte(),
+        ));
+        self.bits(OutputProperty::ValidFlag2, VALID_FLAG2_LED_BRIGHTNESS, true)
+    }
+
+    pub fn set_player_leds_fade(&mut self, fade: bool) -> &mut Self {
+        self.bits(OutputProperty::PlayerLight, PLAYER_LEDS_INSTANT, !fade)
+    }
+
+    pub fn set_mute_led(&mut self, state: MuteLed) -> &mut Self {
+        self.changes
+            .push(OutputChange::Set(OutputProperty::Mute, state.byte()));
+        self
+    }
+
+    pub fn set_power_save(&mut self, power_save: PowerSave) -> &mut Self {
+        self.changes.push(OutputChange::Set(
+            OutputProperty::PowerSave,
+            power_save.byte(),
+        ));
+        self
+    }
+
+    /// Apply the changes to the output bytes, returns whether any byte changed
+    pub(crate) fn write_to(&self, output: &mut HashMap<OutputProperty, u8>) -> bool {
+        self.changes.iter().fold(false, |changed, change| {
+            let value = output.entry(change.property()).or_default();
+            let previous = *value;
+            *value = change.apply(previous);
+            *value != previous || changed
+        })
+    }
+
+    fn trigger_effect(
+        &mut self,
+        properties: &[OutputProperty],
+        effect: TriggerEffect,
+    ) -> &mut Self {
+        match effect.encode() {
+            Ok(block) => self.changes.extend(
+                properties
+                    .iter()
+                    .zip(block)
+                    .map(|(property, value)| OutputChange::Set(*property, value)),
+            ),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+
+    fn bits(&mut self, property: OutputProperty, mask: u8, set: bool) -> &mut Self {
+        self.changes.push(OutputChange::Bits {
+            property,
+            mask,
+            value: if set { mask } else { 0 },
+        });
+        self
+    }
+}
+
+/// Leds lit in a player LEDs byte, without the fade bit
+pub(crate) fn player_leds(light: u8) -> PlayerLeds {
+    PlayerLeds::new(light & PLAYER_LEDS_MASK).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output bytes after applying the batch's changes to `bytes`
+    fn output(
+        mut bytes: HashMap<OutputProperty, u8>,
+        batch: &OutputBatch,
+    ) -> HashMap<OutputProperty, u8> {
+        batch.write_to(&mut bytes);
+        bytes
+    }
+
+    fn player_light(bytes: &HashMap<OutputProperty, u8>) -> u8 {
+        bytes[&OutputProperty::PlayerLight]
+    }
+
+    #[test]
+    fn player_leds_byte() {
+        assert_eq!(OutputProperty::PlayerLight.byte(), 44);
+        for (number, byte) in [
+            (0, 0x00),
+            (1, 0x04),
+            (2, 0x0A),
+            (3, 0x15),
+            (4, 0x1B),
+            (5, 0x1F),
+        ] {
+            let leds = PlayerLeds::player(number).unwrap();
+            let bytes = output(HashMap::new(), OutputBatch::default().set_player_leds(leds));
+            assert_eq!(player_light(&bytes), byte);
+            assert_eq!(player_leds(byte), leds);
+        }
+    }
+
+    #[test]
+    fn player_leds_instant_bit() {
+        let bytes = output(
+            HashMap::new(),
+            OutputBatch::default()
+                .set_player_leds(PlayerLeds::player(3).unwrap())
+                .set_player_leds_fade(false),
+        );
+        assert_eq!(player_light(&bytes), 0x35);
+        assert_eq!(player_leds(0x35), PlayerLeds::player(3).unwrap());
+
+        // changing the LEDs keeps the instant bit, and the other way around
+        let bytes = output(
+            bytes,
+            OutputBatch::default().set_player_leds(PlayerLeds::player(1).unwrap()),
+        );
+        assert_eq!(player_light(&bytes), 0x24);
+        let bytes = output(bytes, OutputBatch::default().set_player_leds_fade(true));
+        assert_eq!(player_light(&bytes), 0x04);
+    }
+
+    #[test]
+    fn player_leds_brightness() {
+        let bytes = output(
+            HashMap::from([(OutputProperty::ValidFlag2, 0x04)]),
+            OutputBatch::default().set_player_leds_brightness(LedBrightness::Low),
+        );
+        assert_eq!(OutputProperty::LedBrightness.byte(), 43);
+        assert_eq!(bytes[&OutputProperty::LedBrightness], 0x02);
+        assert_eq!(bytes[&OutputProperty::ValidFlag2], 0x05);
+    }
+
+    #[test]
+    fn invalid_trigger_effect_is_not_written() {
+        let valid = TriggerEffect::Feedback {
+            start: 2,
+            strength: 5,
+        };
+        let mut batch = OutputBatch::default();
+        batch
+            .set_left_trigger_effect(valid)
+            .set_right_trigger_effect(TriggerEffect::Weapon {
+                start: 1,
+                end: 5,
+                strength: 8,
+            });
+        assert!(batch.error.is_some());
+
+        let mut bytes = HashMap::new();
+        assert!(OutputBatch::default()
+            .set_left_trigger_effect(valid)
+            .write_to(&mut bytes));
+        let before = bytes.clone();
+        let mut invalid = OutputBatch::default();
+        invalid.set_left_trigger_effect(TriggerEffect::Weapon {
+            start: 2,
+            end: 9,
+            strength: 8,
+        });
+        assert!(invalid.error.is_some());
+        assert!(!invalid.write_to(&mut bytes));
+        assert_eq!(bytes, before);
+    }
+
+    #[test]
+    fn changes_are_reported_once() {
+        let mut bytes = HashMap::new();
+        let mut batch = OutputBatch::default();
+        batch
+            .set_mute_led(MuteLed::On)
+            .set_player_leds(PlayerLeds::ALL)
+            .set_player_leds_fade(false)
+            .set_right_trigger_effect(TriggerEffect::Feedback {
+                start: 0,
+                strength: 8,
+            });
+        assert!(batch.write_to(&mut bytes));
+        assert_eq!(bytes[&OutputProperty::PlayerLight], 0x3F);
+        // the same values again change nothing
+        assert!(!batch.write_to(&mut bytes));
+        assert!(!OutputBatch::default()
+            .set_player_leds_fade(false)
+            .set_mute_led(MuteLed::On)
+            .write_to(&mut bytes));
+        assert!(!OutputBatch::default().write_to(&mut bytes));
+        assert!(OutputBatch::default()
+            .set_mute_led(MuteLed::Off)
+            .write_to(&mut bytes));
+    }
+}
@@ -0,0 +1,144 @@
+use std::{error::Error, fmt};
+
+/// Number of white LEDs under the touchpad
+pub const PLAYER_LED_COUNT: u8 = 5;
+const PLAYER_LED_MASK: u8 = (1 << PLAYER_LED_COUNT) - 1;
+
+/// White LEDs under the touchpad, bit 0 being the leftmost LED
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PlayerLeds(u8);
+
+impl PlayerLeds {
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self(PLAYER_LED_MASK);
+
+    /// Light the LEDs whose bits are set, only the lowest 5 bits may be set
+    pub fn new(mask: u8) -> Result<Self, PlayerLedsError> {
+        if mask & !PLAYER_LED_MASK != 0 {
+            return Err(PlayerLedsError::InvalidMask(mask));
+        }
+        Ok(Self(mask))
+    }
+
+    /// Pattern the console uses for a player number in the 0-5 range, 0 meaning no light. Led placement:
+    /// - 0: xxxxx
+    /// - 1: xxOxx
+    /// - 2: xOxOx
+    /// - 3: OxOxO
+    /// - 4: OOxOO
+    /// - 5: OOOOO
+    pub fn player(number: u8) -> Result<Self, PlayerLedsError> {
+        match number {
+            0 => Ok(Self(0x00)),
+            1 => Ok(Self(0x04)),
+            2 => Ok(Self(0x0A)),
+            3 => Ok(Self(0x15)),
+            4 => Ok(Self(0x1B)),
+            5 => Ok(Self(0x1F)),
+            _ => Err(PlayerLedsError::InvalidPlayerNumber(number)),
+        }
+    }
+
+    /// Turn the LED at `index`, 0 being the leftmost, on or off. Indexes past the last LED are ignored
+    pub fn with(self, index: u8, on: bool) -> Self {
+        if index >= PLAYER_LED_COUNT {
+            return self;
+        }
+        if on {
+            Self(self.0 | (1 << index))
+        } else {
+            Self(self.0 & !(1 << index))
+        }
+    }
+
+    pub fn is_on(&self, index: u8) -> bool {
+        index < PLAYER_LED_COUNT && self.0 & (1 << index) != 0
+    }
+
+    pub fn mask(&self) -> u8 {
+        self.0
+    }
+}
+
+/// Brightness of the player LEDs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LedBrightness {
+    #[default]
+    High,
+    Medium,
+    Low,
+}
+
+impl LedBrightness {
+    pub(crate) fn byte(self) -> u8 {
+        match self {
+            LedBrightness::High => 0x00,
+            LedBrightness::Medium => 0x01,
+            LedBrightness::Low => 0x02,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlayerLedsError {
+    /// Player numbers go from 0 to 5
+    InvalidPlayerNumber(u8),
+    /// Bits past the 5th LED were set
+    InvalidMask(u8),
+}
+
+impl fmt::Display for PlayerLedsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerLedsError::InvalidPlayerNumber(number) => {
+                write!(f, "invalid player number {number}, expected 0-5")
+            }
+            PlayerLedsError::InvalidMask(mask) => {
+                write!(f, "invalid player LED mask {mask:#04X}, expected 5 bits")
+            }
+        }
+    }
+}
+
+impl Error for PlayerLedsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// LED placement from left to right, `O` being lit
+    fn placement(leds: PlayerLeds) -> String {
+        (0..PLAYER_LED_COUNT)
+            .map(|index| if leds.is_on(index) { 'O' } else { 'x' })
+            .collect()
+    }
+
+    #[test]
+    fn player_patterns() {
+        let patterns = (0..=5)
+            .map(|number| placement(PlayerLeds::player(number).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            patterns,
+            ["xxxxx", "xxOxx", "xOxOx", "OxOxO", "OOxOO", "OOOOO"]
+        );
+        assert_eq!(PlayerLeds::player(5), Ok(PlayerLeds::ALL));
+        assert_eq!(
+            PlayerLeds::player(6),
+            Err(PlayerLedsError::InvalidPlayerNumber(6))
+        );
+    }
+
+    #[test]
+    fn mask() {
+        assert_eq!(PlayerLeds::new(0x1F), Ok(PlayerLeds::ALL));
+        assert_eq!(
+            PlayerLeds::new(0x20),
+            Err(PlayerLedsError::InvalidMask(0x20))
+        );
+        let leds = PlayerLeds::NONE.with(0, true).with(4, true).with(5, true);
+        assert_eq!(leds.mask(), 0x11);
+        assert_eq!(leds.with(0, false).mask(), 0x10);
+        assert!(!leds.is_on(5));
+    }
+}
@@ -1,8 +1,9 @@
 use std::hash::Hash;
 
 use super::{
-    analog_pad::AnalogPad, dpad::DPad, offset::Offset, symbols::Symbols, trigger::Trigger,
-    valuetype::ValueType,
+    analog_pad::AnalogPad, dpad::DPad, gesture::Gesture, motion::MotionSample, offset::Offset,
+    symbols::Symbols, touch::TouchFrame, touch_zone::ZoneButton, trigger::Trigger,
+    trigger_effect::TRIGGER_EFFECT_SIZE, valuetype::ValueType,
 };
 
 #[derive(Eq, Hash, PartialEq, Clone, Copy)]
@@ -65,19 +66,29 @@ impl InputProperty {
             InputProperty::AccelerationY => Offset::bytes(24..26),
             InputProperty::AccelerationZ => Offset::bytes(26..28),
 
-            InputProperty::TouchPadFinger1Active => Offset::bytes(33..34),
-            InputProperty::TouchPad1Id => Offset::bytes(33..34),
+            InputProperty::TouchPadFinger1Active => Offset::bit(33, 7),
+            InputProperty::TouchPad1Id => Offset::bits(33, 0..7),
             InputProperty::TouchPad1X => Offset::bytes(34..36),
             InputProperty::TouchPad1Y => Offset::bytes(35..37),
-            InputProperty::TouchPadFinger2Active => Offset::bytes(37..38),
-            InputProperty::TouchPad2Id => Offset::bytes(37..38),
+            InputProperty::TouchPadFinger2Active => Offset::bit(37, 7),
+            InputProperty::TouchPad2Id => Offset::bits(37, 0..7),
             InputProperty::TouchPad2X => Offset::bytes(38..40),
             InputProperty::TouchPad2Y => Offset::bytes(39..41),
 
-            InputProperty::R2FeedbackOn => Offset::bytes(42..43),
-            InputProperty::L2FeedbackOn => Offset::bytes(43..44),
-            InputProperty::R2FeedbackValue => Offset::bytes(42..43),
-            InputProperty::L2FeedbackValue => Offset::bytes(43..44),
+            InputProperty::R2FeedbackOn => Offset::bits(48, 0..4),
+            InputProperty::L2FeedbackOn => Offset::bits(48, 4..8),
+            InputProperty::R2FeedbackValue => Offset::bits(42, 0..4),
+            InputProperty::L2FeedbackValue => Offset::bits(43, 0..4),
+        }
+    }
+
+    /// Index of the gyroscope axis read by the property
+    pub(crate) fn gyro_axis(&self) -> Option<usize> {
+        match self {
+            InputProperty::GyroscopeX => Some(0),
+            InputProperty::GyroscopeY => Some(1),
+            InputProperty::GyroscopeZ => Some(2),
+            _ => None,
         }
     }
 
@@ -93,7 +104,7 @@ impl InputProperty {
             | InputProperty::TouchPad
             | InputProperty::PlayStation => ValueType::Bool(*data.first().unwrap() == 1),
 
-            // TODO: normalize values
+            // raw values, `MotionSample` converts them to physical units
             InputProperty::GyroscopeX
             | InputProperty::GyroscopeY
             | InputProperty::GyroscopeZ
@@ -101,27 +112,29 @@ impl InputProperty {
             | InputProperty::AccelerationY
             | InputProperty::AccelerationZ => ValueType::I16(gyro_accel_into_u16(data)),
 
-            InputProperty::TouchPadFinger1Active => ValueType::Bool(data[0] & 0x80 == 0),
-            InputProperty::TouchPad1Id => ValueType::U8(data[0] & 0x7F),
+            // the bit is set while the finger is lifted
+            InputProperty::TouchPadFinger1Active => ValueType::Bool(data[0] == 0),
+            InputProperty::TouchPad1Id => ValueType::U8(data[0]),
             InputProperty::TouchPad1X => {
                 ValueType::U16(((data[1] as u16 & 0x0F) << 8) | data[0] as u16)
             }
             InputProperty::TouchPad1Y => {
                 ValueType::U16(((data[1] as u16) << 4) | (data[0] as u16 & 0xF0) >> 4)
             }
-            InputProperty::TouchPadFinger2Active => ValueType::Bool(data[0] & 0x80 == 0),
-            InputProperty::TouchPad2Id => ValueType::U8(data[0] & 0x7F),
+            InputProperty::TouchPadFinger2Active => ValueType::Bool(data[0] == 0),
+            InputProperty::TouchPad2Id => ValueType::U8(data[0]),
             InputProperty::TouchPad2X => {
                 ValueType::U16((data[1] as u16 & 0x0F) << 8 | data[0] as u16)
             }
             InputProperty::TouchPad2Y => {
                 ValueType::U16(((data[1] as u16) << 4) | (data[0] as u16 & 0xF0) >> 4)
             }
+            // nibble of the effect running on the trigger, see `TriggerFeedbackStatus`
             InputProperty::R2FeedbackOn | InputProperty::L2FeedbackOn => {
-                ValueType::Bool(data[0] & 0x10 == 0x10)
+                ValueType::Bool(data[0] != 0)
             }
             InputProperty::R2FeedbackValue | InputProperty::L2FeedbackValue => {
-                ValueType::U8(data[0] & 0x0F)
+                ValueType::U8(data[0])
             }
         }
     }
@@ -131,12 +144,15 @@ fn gyro_accel_into_u16(data: &[u8]) -> i16 {
     (data[1] as i16) << 8 | data[0] as i16
 }
 
-#[derive(Eq, Hash, PartialEq, Clone, Copy)]
+#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
 pub(crate) enum OutputProperty {
     Red,
     Green,
     Blue,
 
+    RumbleLow,
+    RumbleHigh,
+
     RightEffectMode,
     RightEffectParameter1,
     RightEffectParameter2,
@@ -145,6 +161,9 @@ pub(crate) enum OutputProperty {
     RightEffectParameter5,
     RightEffectParameter6,
     RightEffectParameter7,
+    RightEffectParameter8,
+    RightEffectParameter9,
+    RightEffectParameter10,
 
     LeftEffectMode,
     LeftEffectParameter1,
@@ -154,19 +173,58 @@ pub(crate) enum OutputProperty {
     LeftEffectParameter5,
     LeftEffectParameter6,
     LeftEffectParameter7,
+    LeftEffectParameter8,
+    LeftEffectParameter9,
+    LeftEffectParameter10,
 
     PlayerLight,
+    LedBrightness,
     Mute,
+    PowerSave,
+
+    ValidFlag2,
 }
 
 impl OutputProperty {
+    /// Bytes of the left trigger effect block, in order
+    pub(crate) const LEFT_EFFECT: [Self; TRIGGER_EFFECT_SIZE] = [
+        Self::LeftEffectMode,
+        Self::LeftEffectParameter1,
+        Self::LeftEffectParameter2,
+        Self::LeftEffectParameter3,
+        Self::LeftEffectParameter4,
+        Self::LeftEffectParameter5,
+        Self::LeftEffectParameter6,
+        Self::LeftEffectParameter7,
+        Self::LeftEffectParameter8,
+        Self::LeftEffectParameter9,
+        Self::LeftEffectParameter10,
+    ];
+    /// Bytes of the right trigger effect block, in order
+    pub(crate) const RIGHT_EFFECT: [Self; TRIGGER_EFFECT_SIZE] = [
+        Self::RightEffectMode,
+        Self::RightEffectParameter1,
+        Self::RightEffectParameter2,
+        Self::RightEffectParameter3,
+        Self::RightEffectParameter4,
+        Self::RightEffectParameter5,
+        Self::RightEffectParameter6,
+        Self::RightEffectParameter7,
+        Self::RightEffectParameter8,
+        Self::RightEffectParameter9,
+        Self::RightEffectParameter10,
+    ];
+
     pub(crate) fn byte(self) -> usize {
         match self {
             OutputProperty::Red => 45,
             OutputProperty::Green => 46,
             OutputProperty::Blue => 47,
 
+            OutputProperty::RumbleHigh => 3,
+            OutputProperty::RumbleLow => 4,
             OutputProperty::Mute => 9,
+            OutputProperty::PowerSave => 10,
             OutputProperty::RightEffectMode => 11,
             OutputProperty::RightEffectParameter1 => 12,
             OutputProperty::RightEffectParameter2 => 13,
@@ -175,6 +233,9 @@ impl OutputProperty {
             OutputProperty::RightEffectParameter5 => 16,
             OutputProperty::RightEffectParameter6 => 17,
             OutputProperty::RightEffectParameter7 => 18,
+            OutputProperty::RightEffectParameter8 => 19,
+            OutputProperty::RightEffectParameter9 => 20,
+            OutputProperty::RightEffectParameter10 => 21,
 
             OutputProperty::LeftEffectMode => 22,
             OutputProperty::LeftEffectParameter1 => 23,
@@ -184,8 +245,13 @@ impl OutputProperty {
             OutputProperty::LeftEffectParameter5 => 27,
             OutputProperty::LeftEffectParameter6 => 28,
             OutputProperty::LeftEffectParameter7 => 29,
+            OutputProperty::LeftEffectParameter8 => 30,
+            OutputProperty::LeftEffectParameter9 => 31,
+            OutputProperty::LeftEffectParameter10 => 32,
 
             OutputProperty::PlayerLight => 44,
+            OutputProperty::LedBrightness => 43,
+            OutputProperty::ValidFlag2 => 39,
         }
     }
 }
@@ -200,6 +266,22 @@ pub enum ComboProperty {
     RT(Trigger),
     LeftPad(AnalogPad),
     RightPad(AnalogPad),
+    /// Left stick used as a dpad, `DPad::None` while the stick is in its dead zone
+    LeftPadDirection(DPad),
+    /// Right stick used as a dpad, `DPad::None` while the stick is in its dead zone
+    RightPadDirection(DPad),
+    /// Left trigger used as a digital button
+    LTPressed(bool),
+    /// Right trigger used as a digital button
+    RTPressed(bool),
+    /// Gyroscope, accelerometer and sensor timestamp, received with every report
+    Motion(MotionSample),
+    /// Movement recognized from the accelerometer
+    Gesture(Gesture),
+    /// Both fingers on the touchpad
+    Touch(TouchFrame),
+    /// Zone of the touchpad used as a button, each zone id being a separate input
+    TouchZone(ZoneButton),
 }
 
 impl ComboProperty {
@@ -213,60 +295,67 @@ impl ComboProperty {
             ComboProperty::RT(_) => Self::RT(Trigger::new(0)),
             ComboProperty::LeftPad(_) => Self::LeftPad(AnalogPad::new(0, 0)),
             ComboProperty::RightPad(_) => Self::RightPad(AnalogPad::new(0, 0)),
+            ComboProperty::LeftPadDirection(_) => Self::LeftPadDirection(DPad::None),
+            ComboProperty::RightPadDirection(_) => Self::RightPadDirection(DPad::None),
+            ComboProperty::LTPressed(_) => Self::LTPressed(false),
+            ComboProperty::RTPressed(_) => Self::RTPressed(false),
+            ComboProperty::Motion(_) => Self::Motion(MotionSample::default()),
+            ComboProperty::Gesture(_) => Self::Gesture(Gesture::None),
+            ComboProperty::Touch(_) => Self::Touch(TouchFrame::default()),
+            ComboProperty::TouchZone(button) => Self::TouchZone(ZoneButton {
+                id: button.id,
+                pressed: false,
+            }),
         }
     }
 
     pub(crate) fn offset(self) -> Offset {
         match self {
-            ComboProperty::LeftPad(_) => Offset::bytes(1..3),
-            ComboProperty::RightPad(_) => Offset::bytes(3..5),
+            ComboProperty::LeftPad(_) | ComboProperty::LeftPadDirection(_) => Offset::bytes(1..3),
+            ComboProperty::RightPad(_) | ComboProperty::RightPadDirection(_) => Offset::bytes(3..5),
             ComboProperty::Symbol(_) => Offset::bits(8, 4..8),
             ComboProperty::DPad(_) => Offset::bits(8, 0..4),
             ComboProperty::LB(_) => Offset::bit(9, 0),
             ComboProperty::RB(_) => Offset::bit(9, 1),
-            ComboProperty::LT(_) => Offset::byte(5),
-            ComboProperty::RT(_) => Offset::byte(6),
+            ComboProperty::LT(_) | ComboProperty::LTPressed(_) => Offset::byte(5),
+            ComboProperty::RT(_) | ComboProperty::RTPressed(_) => Offset::byte(6),
+            ComboProperty::Motion(_) => Offset::bytes(16..32),
+            ComboProperty::Gesture(_) => Offset::bytes(22..28),
+            ComboProperty::Touch(_) | ComboProperty::TouchZone(_) => Offset::bytes(33..41),
         }
     }
 
-    // TODO: there must be a better way to do this...
-
     pub(crate) fn to_dpad(self) -> DPad {
         match self {
-            ComboProperty::Symbol(_) => todo!(),
-            ComboProperty::DPad(dpad) => dpad,
-            ComboProperty::LB(_) => todo!(),
-            ComboProperty::RB(_) => todo!(),
-            ComboProperty::LT(_) => todo!(),
-            ComboProperty::RT(_) => todo!(),
-            ComboProperty::LeftPad(_) => todo!(),
-            ComboProperty::RightPad(_) => todo!(),
+            ComboProperty::DPad(dpad)
+            | ComboProperty::LeftPadDirection(dpad)
+            | ComboProperty::RightPadDirection(dpad) => dpad,
+            _ => unreachable!("{self:?} is not a dpad"),
         }
     }
 
     pub(crate) fn to_symbols(self) -> Symbols {
         match self {
-            ComboProperty::Symbol(sym) => sym,
-            ComboProperty::DPad(_) => todo!(),
-            ComboProperty::LB(_) => todo!(),
-            ComboProperty::RB(_) => todo!(),
-            ComboProperty::LT(_) => todo!(),
-            ComboProperty::RT(_) => todo!(),
-            ComboProperty::LeftPad(_) => todo!(),
-            ComboProperty::RightPad(_) => todo!(),
+            ComboProperty::Symbol(symbols) => symbols,
+            _ => unreachable!("{self:?} is not a symbol button"),
         }
     }
 
     pub(crate) fn to_trigger(self) -> Trigger {
         match self {
-            ComboProperty::Symbol(_) => todo!(),
-            ComboProperty::DPad(_) => todo!(),
-            ComboProperty::LB(_) => todo!(),
-            ComboProperty::RB(_) => todo!(),
-            ComboProperty::LT(v) => v,
-            ComboProperty::RT(v) => v,
-            ComboProperty::LeftPad(_) => todo!(),
-            ComboProperty::RightPad(_) => todo!(),
+            ComboProperty::LT(trigger) | ComboProperty::RT(trigger) => trigger,
+            _ => unreachable!("{self:?} is not a trigger"),
+        }
+    }
+
+    pub(crate) fn to_bool(self) -> bool {
+        match self {
+            ComboProperty::LB(pressed)
+            | ComboProperty::RB(pressed)
+            | ComboProperty::LTPressed(pressed)
+            | ComboProperty::RTPressed(pressed) => pressed,
+            ComboProperty::TouchZone(button) => button.pressed,
+            _ => unreachable!("{self:?} is not a button"),
         }
     }
 }
@@ -282,6 +371,99 @@ impl ComboProperty {
             ComboProperty::RightPad(_) => ComboProperty::RightPad(AnalogPad::new(data[0], data[1])),
             ComboProperty::LT(_) => ComboProperty::LT(Trigger::new(data[0])),
             ComboProperty::RT(_) => ComboProperty::RT(Trigger::new(data[0])),
+            ComboProperty::Motion(_) => ComboProperty::Motion(MotionSample::new(data)),
+            ComboProperty::Touch(_) => ComboProperty::Touch(TouchFrame::new(data)),
+            ComboProperty::LeftPadDirection(_)
+            | ComboProperty::RightPadDirection(_)
+            | ComboProperty::LTPressed(_)
+            | ComboProperty::RTPressed(_)
+            | ComboProperty::Gesture(_)
+            | ComboProperty::TouchZone(_) => {
+                unreachable!("{self:?} is derived from other inputs by InputSettings::decode")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DualSense;
+
+    /// Decode a property the way the input report is read for the raw callbacks
+    fn decode(prop: InputProperty, data: &[u8; 64]) -> ValueType {
+        let offset = prop.offset();
+        if offset.is_whole_byte() {
+            prop.convert(&data[offset.bytes])
+        } else {
+            prop.convert(&[DualSense::extract_bits(&offset, data)])
         }
     }
+
+    fn report() -> [u8; 64] {
+        let mut data = [0; 64];
+        // first finger: id 5, touching, x 0x5A3, y 0x2C7
+        data[33..37].copy_from_slice(&[0x05, 0xA3, 0x75, 0x2C]);
+        // second finger: id 0x42, lifted, x 0x0FF, y 0x437
+        data[37..41].copy_from_slice(&[0xC2, 0xFF, 0x70, 0x43]);
+        data
+    }
+
+    #[test]
+    fn touch_points() {
+        let data = report();
+        assert_eq!(
+            decode(InputProperty::TouchPadFinger1Active, &data),
+            ValueType::Bool(true)
+        );
+        assert_eq!(decode(InputProperty::TouchPad1Id, &data), ValueType::U8(5));
+        assert_eq!(
+            decode(InputProperty::TouchPad1X, &data),
+            ValueType::U16(0x5A3)
+        );
+        assert_eq!(
+            decode(InputProperty::TouchPad1Y, &data),
+            ValueType::U16(0x2C7)
+        );
+        assert_eq!(
+            decode(InputProperty::TouchPadFinger2Active, &data),
+            ValueType::Bool(false)
+        );
+        assert_eq!(
+            decode(InputProperty::TouchPad2Id, &data),
+            ValueType::U8(0x42)
+        );
+        assert_eq!(
+            decode(InputProperty::TouchPad2X, &data),
+            ValueType::U16(0x0FF)
+        );
+        assert_eq!(
+            decode(InputProperty::TouchPad2Y, &data),
+            ValueType::U16(0x437)
+        );
+    }
+
+    #[test]
+    fn touch_points_match_the_touch_frame() {
+        let data = report();
+        let frame =
+            TouchFrame::new(&data[ComboProperty::Touch(TouchFrame::default()).offset().bytes]);
+        let [first, second] = frame.touches;
+        assert_eq!(
+            decode(InputProperty::TouchPad1X, &data),
+            ValueType::U16(first.x)
+        );
+        assert_eq!(
+            decode(InputProperty::TouchPad1Y, &data),
+            ValueType::U16(first.y)
+        );
+        assert_eq!(
+            decode(InputProperty::TouchPad2Id, &data),
+            ValueType::U8(second.id)
+        );
+        assert_eq!(
+            decode(InputProperty::TouchPadFinger2Active, &data),
+            ValueType::Bool(second.active)
+        );
+    }
 }
@@ -0,0 +1,160 @@
+use std::sync::{Arc, Mutex};
+
+use super::motion::TIMESTAMP_TICKS_PER_SECOND;
+
+/// Byte of the input report holding the sequence counter
+pub(crate) const SEQUENCE_BYTE: usize = 7;
+/// First byte of the input report holding the sensor timestamp
+const TIMESTAMP_BYTE: usize = 28;
+/// Weight of each report in the average time between reports
+const RATE_SMOOTHING: f32 = 0.05;
+
+/// Sequence counter and sensor timestamp identifying an input report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ReportStamp {
+    /// Incremented by the controller with every report, wrapping around
+    pub sequence: u8,
+    /// Counting in units of 1/3 microseconds, wrapping around
+    pub timestamp: u32,
+}
+
+impl ReportStamp {
+    pub(crate) fn new(data: &[u8]) -> Self {
+        let timestamp = &data[TIMESTAMP_BYTE..TIMESTAMP_BYTE + 4];
+        Self {
+            sequence: data[SEQUENCE_BYTE],
+            timestamp: u32::from_le_bytes(timestamp.try_into().unwrap()),
+        }
+    }
+}
+
+/// Stamp of the report whose values are being handed to callbacks, see `DualSense::current_report`
+#[derive(Clone, Debug, Default)]
+pub struct CurrentReport {
+    stamp: Arc<Mutex<ReportStamp>>,
+}
+
+impl CurrentReport {
+    /// Stamp of the report being dispatched, or of the last one once all its callbacks were called
+    pub fn stamp(&self) -> ReportStamp {
+        *self.stamp.lock().unwrap()
+    }
+
+    pub(crate) fn set(&self, stamp: ReportStamp) {
+        *self.stamp.lock().unwrap() = stamp;
+    }
+}
+
+/// Counters about the input reports received from the controller. Dropped reports are detected from gaps in the
+/// report sequence counter, the rate is measured with the sensor timestamps
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReportStats {
+    reports: u64,
+    dropped: u64,
+    average_interval: f32,
+    sequence: u8,
+    timestamp: u32,
+}
+
+impl ReportStats {
+    pub(crate) fn update(&mut self, sequence: u8, timestamp: u32) {
+        if self.reports > 0 {
+            // a counter that didn't move is a repeated report, not 255 lost ones
+            let missing = if sequence != self.sequence {
+                sequence.wrapping_sub(self.sequence).wrapping_sub(1)
+            } else {
+                0
+            };
+            self.dropped += missing as u64;
+
+            // the dropped reports were sent in between, so the controller's interval is shorter
+            let interval = timestamp.wrapping_sub(self.timestamp) as f32
+                / TIMESTAMP_TICKS_PER_SECOND
+                / (missing as f32 + 1.0);
+            self.average_interval = if self.average_interval == 0.0 {
+                interval
+            } else {
+                self.average_interval + (interval - self.average_interval) * RATE_SMOOTHING
+            };
+        }
+        self.reports += 1;
+        self.sequence = sequence;
+        self.timestamp = timestamp;
+    }
+
+    /// Number of reports received
+    pub fn reports(&self) -> u64 {
+        self.reports
+    }
+
+    /// Number of reports the controller sent that were never received
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Reports sent by the controller per second, including the dropped ones, 0 until two reports were received
+    pub fn report_rate(&self) -> f32 {
+        if self.average_interval > 0.0 {
+            1.0 / self.average_interval
+        } else {
+            0.0
+        }
+    }
+
+    /// Sequence counter of the last report, incremented by the controller with every report and wrapping around
+    pub fn sequence(&self) -> u8 {
+        self.sequence
+    }
+
+    /// Sensor timestamp of the last report, counting in units of 1/3 microseconds and wrapping around
+    pub fn timestamp(&self) -> u32 {
+        self.timestamp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sensor timestamp ticks between two reports at 250 Hz
+    const TICKS: u32 = 12_000;
+
+    #[test]
+    fn stamp() {
+        let mut data = [0; 64];
+        data[SEQUENCE_BYTE] = 42;
+        data[TIMESTAMP_BYTE..TIMESTAMP_BYTE + 4].copy_from_slice(&0x1234_5678_u32.to_le_bytes());
+        assert_eq!(
+            ReportStamp::new(&data),
+            ReportStamp {
+                sequence: 42,
+                timestamp: 0x1234_5678
+            }
+        );
+    }
+
+    #[test]
+    fn counts_dropped_reports() {
+        let mut stats = ReportStats::default();
+        for (sequence, timestamp) in [(254, 0), (255, TICKS), (2, 4 * TICKS), (2, 4 * TICKS)] {
+            stats.update(sequence, timestamp);
+        }
+        assert_eq!(stats.reports(), 4);
+        assert_eq!(stats.dropped(), 2);
+        assert_eq!(stats.sequence(), 2);
+        assert_eq!(stats.timestamp(), 4 * TICKS);
+    }
+
+    #[test]
+    fn rate_includes_dropped_reports() {
+        let mut stats = ReportStats::default();
+        assert_eq!(stats.report_rate(), 0.0);
+        // every other report is lost, the counter and the timestamp wrap around
+        let start = u32::MAX - 50 * TICKS;
+        for i in 0..100_u32 {
+            stats.update((i * 2) as u8, start.wrapping_add(i * 2 * TICKS));
+        }
+        assert_eq!(stats.dropped(), 99);
+        assert!((stats.report_rate() - 250.0).abs() < 0.1);
+    }
+}
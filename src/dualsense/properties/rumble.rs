@@ -0,0 +1,279 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+/// Intensity of the two rumble motors
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rumble {
+    /// Left motor, heavy and slow
+    pub low: u8,
+    /// Right motor, light and fast
+    pub high: u8,
+}
+
+impl Rumble {
+    pub const OFF: Self = Self::new(0, 0);
+
+    pub const fn new(low: u8, high: u8) -> Self {
+        Self { low, high }
+    }
+}
+
+/// Motor intensities held for some time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RumbleStep {
+    pub rumble: Rumble,
+    pub seconds: f32,
+}
+
+/// Sequence of rumble steps. Intensities are computed only from the time elapsed since the pattern started, so
+/// the same time always gives the same intensities
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RumblePattern {
+    steps: Vec<RumbleStep>,
+    looping: bool,
+}
+
+impl RumblePattern {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step after the previous ones
+    pub fn step(mut self, rumble: Rumble, seconds: f32) -> Self {
+        self.steps.push(RumbleStep {
+            rumble,
+            seconds: seconds.max(0.0),
+        });
+        self
+    }
+
+    /// Restart from the first step after the last one instead of stopping
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn steps(&self) -> &[RumbleStep] {
+        &self.steps
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Sum of the step durations, in seconds
+    pub fn duration(&self) -> f32 {
+        self.steps.iter().map(|step| step.seconds).sum()
+    }
+
+    /// Whether a one-shot pattern went through all its steps `seconds` after it started
+    pub fn is_finished(&self, seconds: f32) -> bool {
+        !self.looping && seconds >= self.duration()
+    }
+
+    /// Intensities `seconds` after the pattern started, off once a one-shot pattern is finished
+    pub fn rumble_at(&self, seconds: f32) -> Rumble {
+        let duration = self.duration();
+        let mut seconds = if self.looping && duration > 0.0 {
+            seconds.max(0.0) % duration
+        } else {
+            seconds.max(0.0)
+        };
+        for step in &self.steps {
+            if seconds < step.seconds {
+                return step.rumble;
+            }
+            seconds -= step.seconds;
+        }
+        Rumble::OFF
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PlayingPattern {
+    id: u32,
+    pattern: RumblePattern,
+    started: Instant,
+}
+
+/// Rumble set by the user and the pattern playing over it
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RumblePlayer {
+    pub(crate) rumble: Rumble,
+    pattern: Option<PlayingPattern>,
+    next_id: u32,
+}
+
+impl RumblePlayer {
+    /// Intensities written to the output report
+    pub(crate) fn output(&self, now: Instant) -> Rumble {
+        match &self.pattern {
+            Some(playing) => playing
+                .pattern
+                .rumble_at(now.duration_since(playing.started).as_secs_f32()),
+            None => self.rumble,
+        }
+    }
+
+    /// Replace the playing pattern, returns the id of the new one
+    pub(crate) fn play(&mut self, pattern: RumblePattern, now: Instant) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.pattern = Some(PlayingPattern {
+            id,
+            pattern,
+            started: now,
+        });
+        id
+    }
+
+    /// Stop the pattern with the given id, or any pattern for `None`
+    pub(crate) fn stop(&mut self, id: Option<u32>) {
+        if self
+            .pattern
+            .as_ref()
+            .is_some_and(|playing| id.is_none_or(|id| id == playing.id))
+        {
+            self.pattern = None;
+        }
+    }
+
+    pub(crate) fn is_playing(&self, id: u32) -> bool {
+        self.pattern
+            .as_ref()
+            .is_some_and(|playing| playing.id == id)
+    }
+
+    /// Advance the pattern, returns the intensities to send. The motors go back to `Self::rumble` once a one-shot
+    /// pattern is finished
+    pub(crate) fn tick(&mut self, now: Instant) -> Rumble {
+        if self.pattern.as_ref().is_some_and(|playing| {
+            playing
+                .pattern
+                .is_finished(now.duration_since(playing.started).as_secs_f32())
+        }) {
+            self.pattern = None;
+        }
+        self.output(now)
+    }
+}
+
+/// Pattern playing on the rumble motors, see `DualSense::play_rumble_pattern`
+#[derive(Clone, Debug)]
+pub struct RumbleHandle {
+    id: u32,
+    player: Arc<Mutex<RumblePlayer>>,
+}
+
+impl RumbleHandle {
+    pub(crate) fn new(id: u32, player: Arc<Mutex<RumblePlayer>>) -> Self {
+        Self { id, player }
+    }
+
+    /// Stop the pattern, does nothing if another pattern replaced it
+    pub fn stop(&self) {
+        self.player.lock().unwrap().stop(Some(self.id));
+    }
+
+    /// Whether the pattern is still playing: it was not stopped, replaced or, for one-shot patterns, finished
+    pub fn is_playing(&self) -> bool {
+        self.player.lock().unwrap().is_playing(self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn at(start: Instant, seconds: f32) -> Instant {
+        start + Duration::from_secs_f32(seconds)
+    }
+
+    #[test]
+    fn timed_rumble() {
+        let start = Instant::now();
+        let mut player = RumblePlayer {
+            rumble: Rumble::new(10, 20),
+            ..Default::default()
+        };
+        // as played by `DualSense::rumble_for`
+        let id = player.play(RumblePattern::new().step(Rumble::new(200, 100), 0.5), start);
+        assert_eq!(player.tick(start), Rumble::new(200, 100));
+        assert_eq!(player.tick(at(start, 0.49)), Rumble::new(200, 100));
+        assert!(player.is_playing(id));
+        assert_eq!(player.tick(at(start, 0.5)), Rumble::new(10, 20));
+        assert!(!player.is_playing(id));
+        assert_eq!(player.tick(at(start, 2.0)), Rumble::new(10, 20));
+    }
+
+    #[test]
+    fn pattern_steps() {
+        let pattern = RumblePattern::new()
+            .step(Rumble::new(255, 0), 0.1)
+            .step(Rumble::OFF, 0.2)
+            .step(Rumble::new(0, 128), 0.3);
+        assert!((pattern.duration() - 0.6).abs() < 1e-6);
+        let motors = [0.0, 0.05, 0.15, 0.29, 0.35, 0.59, 0.6, 10.0].map(|t| pattern.rumble_at(t));
+        assert_eq!(
+            motors,
+            [
+                Rumble::new(255, 0),
+                Rumble::new(255, 0),
+                Rumble::OFF,
+                Rumble::OFF,
+                Rumble::new(0, 128),
+                Rumble::new(0, 128),
+                Rumble::OFF,
+                Rumble::OFF,
+            ]
+        );
+        assert!(!pattern.is_finished(0.59));
+        assert!(pattern.is_finished(0.6));
+    }
+
+    #[test]
+    fn looping_pattern() {
+        let pattern = RumblePattern::new()
+            .step(Rumble::new(100, 100), 0.25)
+            .step(Rumble::OFF, 0.25)
+            .looping(true);
+        let motors = [0.1, 0.3, 0.6, 0.8, 10.1].map(|t| pattern.rumble_at(t));
+        assert_eq!(
+            motors,
+            [
+                Rumble::new(100, 100),
+                Rumble::OFF,
+                Rumble::new(100, 100),
+                Rumble::OFF,
+                Rumble::new(100, 100),
+            ]
+        );
+        assert!(!pattern.is_finished(100.0));
+        assert_eq!(
+            RumblePattern::new().looping(true).rumble_at(1.0),
+            Rumble::OFF
+        );
+    }
+
+    #[test]
+    fn replacing_and_stopping_patterns() {
+        let start = Instant::now();
+        let mut player = RumblePlayer::default();
+        let first = player.play(RumblePattern::new().step(Rumble::new(1, 1), 1.0), start);
+        let second = player.play(
+            RumblePattern::new().step(Rumble::new(2, 2), 1.0),
+            at(start, 0.5),
+        );
+        assert!(!player.is_playing(first));
+        assert_eq!(player.tick(at(start, 1.2)), Rumble::new(2, 2));
+        // stopping a replaced pattern does nothing
+        player.stop(Some(first));
+        assert!(player.is_playing(second));
+        player.stop(None);
+        assert_eq!(player.tick(at(start, 1.2)), Rumble::OFF);
+    }
+}
@@ -1,10 +1,11 @@
 /// Symbols values
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
 pub enum Symbols {
     Square = 1,
     Cross = 2,
     Circle = 4,
     Triangle = 8,
+    #[default]
     None = 0,
 }
 
@@ -19,9 +20,3 @@ impl From<u8> for Symbols {
         }
     }
 }
-
-impl Default for Symbols {
-    fn default() -> Self {
-        Self::None
-    }
-}
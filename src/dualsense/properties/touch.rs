@@ -0,0 +1,229 @@
+use super::property::ComboProperty;
+
+/// Horizontal resolution of the touchpad
+pub const TOUCHPAD_WIDTH: u16 = 1920;
+/// Vertical resolution of the touchpad
+pub const TOUCHPAD_HEIGHT: u16 = 1080;
+
+/// Finger on the touchpad. The id is assigned by the controller when the finger touches the pad and kept until
+/// it is lifted. Coordinates start from the top left corner
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Touch {
+    pub id: u8,
+    pub active: bool,
+    pub x: u16,
+    pub y: u16,
+}
+
+impl Touch {
+    /// Decode the 4 bytes of a touch point
+    pub(crate) fn new(data: &[u8]) -> Self {
+        Self {
+            id: data[0] & 0x7F,
+            active: data[0] & 0x80 == 0,
+            x: ((data[2] as u16 & 0x0F) << 8) | data[1] as u16,
+            y: ((data[3] as u16) << 4) | ((data[2] as u16 & 0xF0) >> 4),
+        }
+    }
+
+    /// Coordinates in the [0, 1] interval, from the top left corner
+    pub fn normalize(&self) -> (f32, f32) {
+        (
+            (self.x as f32 / (TOUCHPAD_WIDTH - 1) as f32).clamp(0.0, 1.0),
+            (self.y as f32 / (TOUCHPAD_HEIGHT - 1) as f32).clamp(0.0, 1.0),
+        )
+    }
+}
+
+/// Both fingers the touchpad tracks, read from the same report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TouchFrame {
+    pub touches: [Touch; 2],
+}
+
+impl TouchFrame {
+    /// Decode the 8 bytes holding both touch points
+    pub(crate) fn new(data: &[u8]) -> Self {
+        Self {
+            touches: [Touch::new(&data[0..4]), Touch::new(&data[4..8])],
+        }
+    }
+
+    /// Fingers currently on the touchpad
+    pub fn active(&self) -> impl Iterator<Item = &Touch> {
+        self.touches.iter().filter(|touch| touch.active)
+    }
+
+    /// Finger currently on the touchpad with the given id
+    pub fn find(&self, id: u8) -> Option<&Touch> {
+        self.active().find(|touch| touch.id == id)
+    }
+}
+
+impl From<ComboProperty> for TouchFrame {
+    fn from(value: ComboProperty) -> Self {
+        match value {
+            ComboProperty::Touch(v) => v,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Change of a single finger between two reports
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TouchEvent {
+    /// A finger touched the touchpad
+    Down(Touch),
+    /// A finger on the touchpad moved
+    Move(Touch),
+    /// A finger was lifted, with its last position
+    Up(Touch),
+}
+
+/// Compares consecutive frames to find which fingers touched, moved or were lifted, matching them by id
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TouchTracker {
+    previous: TouchFrame,
+}
+
+impl TouchTracker {
+    /// Feed the next frame, returns the events in the order they happened: lifted fingers first
+    pub fn update(&mut self, frame: TouchFrame) -> Vec<TouchEvent> {
+        let mut events = Vec::new();
+        for touch in self.previous.active() {
+            if frame.find(touch.id).is_none() {
+                events.push(TouchEvent::Up(*touch));
+            }
+        }
+        for touch in frame.active() {
+            match self.previous.find(touch.id) {
+                None => events.push(TouchEvent::Down(*touch)),
+                Some(previous) if (previous.x, previous.y) != (touch.x, touch.y) => {
+                    events.push(TouchEvent::Move(*touch))
+                }
+                Some(_) => {}
+            }
+        }
+        self.previous = frame;
+        events
+    }
+
+    /// Last frame given to `Self::update`
+    pub fn frame(&self) -> TouchFrame {
+        self.previous
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Touch point bytes as sent by the controller
+    fn point(id: u8, active: bool, x: u16, y: u16) -> [u8; 4] {
+        [
+            id | if active { 0 } else { 0x80 },
+            x as u8,
+            ((x >> 8) as u8 & 0x0F) | ((y as u8 & 0x0F) << 4),
+            (y >> 4) as u8,
+        ]
+    }
+
+    fn frame(first: [u8; 4], second: [u8; 4]) -> TouchFrame {
+        TouchFrame::new(&[first, second].concat())
+    }
+
+    #[test]
+    fn point_bit_packing() {
+        let touch = Touch::new(&[0x05, 0xA3, 0x75, 0x2C]);
+        assert_eq!(
+            touch,
+            Touch {
+                id: 5,
+                active: true,
+                x: 0x5A3,
+                y: 0x2C7,
+            }
+        );
+        let touch = Touch::new(&[0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(
+            (touch.id, touch.active, touch.x, touch.y),
+            (0x7F, false, 0xFFF, 0xFFF)
+        );
+        for (x, y) in [(0, 0), (1919, 1079), (1, 16), (256, 15), (960, 540)] {
+            let touch = Touch::new(&point(3, true, x, y));
+            assert_eq!((touch.x, touch.y), (x, y));
+        }
+    }
+
+    #[test]
+    fn normalize_corners() {
+        assert_eq!(Touch::new(&point(0, true, 0, 0)).normalize(), (0.0, 0.0));
+        assert_eq!(
+            Touch::new(&point(0, true, 1919, 1079)).normalize(),
+            (1.0, 1.0)
+        );
+        assert_eq!(
+            Touch::new(&point(0, true, 4095, 4095)).normalize(),
+            (1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn tracker_down_move_up() {
+        let lifted = point(1, false, 500, 500);
+        let mut tracker = TouchTracker::default();
+        assert_eq!(tracker.update(frame(lifted, lifted)), vec![]);
+
+        let down = Touch::new(&point(1, true, 100, 200));
+        assert_eq!(
+            tracker.update(frame(point(1, true, 100, 200), lifted)),
+            vec![TouchEvent::Down(down)]
+        );
+        // unchanged position
+        assert_eq!(
+            tracker.update(frame(point(1, true, 100, 200), lifted)),
+            vec![]
+        );
+
+        let moved = Touch::new(&point(1, true, 150, 180));
+        let second = Touch::new(&point(2, true, 900, 900));
+        assert_eq!(
+            tracker.update(frame(point(1, true, 150, 180), point(2, true, 900, 900))),
+            vec![TouchEvent::Move(moved), TouchEvent::Down(second)]
+        );
+
+        // the first finger is lifted, the second one moves into its slot
+        let moved_second = Touch::new(&point(2, true, 910, 900));
+        assert_eq!(
+            tracker.update(frame(point(2, true, 910, 900), point(1, false, 150, 180))),
+            vec![TouchEvent::Up(moved), TouchEvent::Move(moved_second)]
+        );
+        assert_eq!(
+            tracker.update(frame(lifted, lifted)),
+            vec![TouchEvent::Up(moved_second)]
+        );
+        assert_eq!(tracker.frame(), frame(lifted, lifted));
+    }
+
+    #[test]
+    fn tracker_id_reuse() {
+        let mut tracker = TouchTracker::default();
+        tracker.update(frame(point(7, true, 100, 100), point(0, false, 0, 0)));
+        // a new finger in the same slot with another id is a different touch
+        let first = Touch::new(&point(7, true, 100, 100));
+        let second = Touch::new(&point(8, true, 100, 100));
+        assert_eq!(
+            tracker.update(frame(point(8, true, 100, 100), point(0, false, 0, 0))),
+            vec![TouchEvent::Up(first), TouchEvent::Down(second)]
+        );
+        // touching again after being lifted is a new touch, even with the same id
+        assert_eq!(
+            tracker.update(frame(point(8, false, 100, 100), point(0, false, 0, 0))),
+            vec![TouchEvent::Up(second)]
+        );
+        assert_eq!(
+            tracker.update(frame(point(8, true, 300, 100), point(0, false, 0, 0))),
+            vec![TouchEvent::Down(Touch::new(&point(8, true, 300, 100)))]
+        );
+    }
+}
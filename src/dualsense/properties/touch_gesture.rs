@@ -0,0 +1,548 @@
+use super::touch::{Touch, TouchFrame};
+
+/// Direction a finger moved in, Y growing downwards like the touchpad coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Gesture made on the touchpad. Positions and distances are in touchpad units, see `TOUCHPAD_WIDTH`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TouchGesture {
+    /// A finger touched the touchpad briefly without moving
+    Tap { x: f32, y: f32 },
+    /// A second tap close to the previous one, reported instead of a second `Self::Tap`
+    DoubleTap { x: f32, y: f32 },
+    /// A finger was held without moving, reported once while it is still down
+    LongPress { x: f32, y: f32 },
+    /// A finger moved quickly and was lifted, with its average speed in units/second
+    Swipe {
+        direction: SwipeDirection,
+        velocity: f32,
+    },
+    /// Two fingers moving together, with the movement since the previous report. The first one also includes the
+    /// movement made before the gesture was recognized
+    Scroll { dx: f32, dy: f32 },
+    /// Two fingers moving apart or closer, with the ratio of their distance to the one in the previous report, or
+    /// to the starting one for the first pinch
+    Pinch { scale: f32 },
+    /// Two fingers turning around each other, with the clockwise rotation since the previous report in degrees, or
+    /// since the fingers touched for the first rotation
+    Rotate { degrees: f32 },
+}
+
+/// What the two fingers were recognized as doing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TwoFingerMode {
+    Undecided,
+    Scroll,
+    Pinch,
+    Rotate,
+}
+
+/// Recognizes touchpad gestures from consecutive touch frames. Once two fingers touch the pad, single finger
+/// gestures are ignored until all fingers are lifted. The two finger gesture is chosen by whichever of the
+/// movement, the distance change or the rotation first passes its threshold, and kept until the fingers are lifted
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TouchGestureRecognizer {
+    tap_seconds: f32,
+    tap_slop: f32,
+    double_tap_seconds: f32,
+    long_press_seconds: f32,
+    swipe_distance: f32,
+    swipe_velocity: f32,
+    scroll_distance: f32,
+    pinch_ratio: f32,
+    rotate_degrees: f32,
+
+    start: Option<Touch>,
+    last: Option<Touch>,
+    held_seconds: f32,
+    moved: bool,
+    long_pressed: bool,
+    multi_touch: bool,
+    last_tap: Option<(f32, f32)>,
+    since_tap: f32,
+
+    mode: TwoFingerMode,
+    two_finger_start: Option<FingerPair>,
+    two_finger_last: Option<FingerPair>,
+}
+
+impl TouchGestureRecognizer {
+    pub fn new() -> Self {
+        Self {
+            tap_seconds: 0.25,
+            tap_slop: 40.0,
+            double_tap_seconds: 0.3,
+            long_press_seconds: 0.6,
+            swipe_distance: 300.0,
+            swipe_velocity: 1000.0,
+            scroll_distance: 40.0,
+            pinch_ratio: 0.1,
+            rotate_degrees: 15.0,
+            start: None,
+            last: None,
+            held_seconds: 0.0,
+            moved: false,
+            long_pressed: false,
+            multi_touch: false,
+            last_tap: None,
+            since_tap: f32::INFINITY,
+            mode: TwoFingerMode::Undecided,
+            two_finger_start: None,
+            two_finger_last: None,
+        }
+    }
+
+    /// A tap is shorter than `seconds` and moves less than `slop` units
+    pub fn tap(mut self, seconds: f32, slop: f32) -> Self {
+        self.tap_seconds = seconds;
+        self.tap_slop = slop;
+        self
+    }
+
+    /// Two taps less than `seconds` apart and within the tap slop make a double tap
+    pub fn double_tap(mut self, seconds: f32) -> Self {
+        self.double_tap_seconds = seconds;
+        self
+    }
+
+    /// A finger held for `seconds` without moving makes a long press
+    pub fn long_press(mut self, seconds: f32) -> Self {
+        self.long_press_seconds = seconds;
+        self
+    }
+
+    /// A swipe covers at least `distance` units at `velocity` units/second or faster
+    pub fn swipe(mut self, distance: f32, velocity: f32) -> Self {
+        self.swipe_distance = distance;
+        self.swipe_velocity = velocity;
+        self
+    }
+
+    /// Two fingers scroll once they moved `scroll_distance` units together, pinch once their distance changed by
+    /// `pinch_ratio` (0.1 being 10%) and rotate once they turned `rotate_degrees`
+    pub fn two_finger(
+        mut self,
+        scroll_distance: f32,
+        pinch_ratio: f32,
+        rotate_degrees: f32,
+    ) -> Self {
+        self.scroll_distance = scroll_distance;
+        self.pinch_ratio = pinch_ratio;
+        self.rotate_degrees = rotate_degrees;
+        self
+    }
+
+    /// Feed the next frame and the seconds elapsed since the previous one
+    pub fn update(&mut self, frame: TouchFrame, dt: f32) -> Vec<TouchGesture> {
+        let mut gestures = Vec::new();
+        self.since_tap += dt;
+        let mut active = frame.active();
+
+        match (active.next(), active.next()) {
+            (None, _) => {
+                if let (Some(start), Some(last)) = (self.start, self.last) {
+                    if !self.multi_touch {
+                        gestures.extend(self.finger_lifted(start, last));
+                    }
+                }
+                self.reset_contact();
+            }
+            (Some(touch), None) => {
+                self.two_finger_start = None;
+                self.two_finger_last = None;
+                if self.start.map(|start| start.id) != Some(touch.id) {
+                    self.start = Some(*touch);
+                    self.held_seconds = 0.0;
+                    self.moved = false;
+                    self.long_pressed = false;
+                } else {
+                    self.held_seconds += dt;
+                }
+                self.last = Some(*touch);
+
+                let start = self.start.unwrap_or(*touch);
+                if distance(&start, touch) > self.tap_slop {
+                    self.moved = true;
+                }
+                if !self.multi_touch
+                    && !self.moved
+                    && !self.long_pressed
+                    && self.held_seconds >= self.long_press_seconds
+                {
+                    self.long_pressed = true;
+                    gestures.push(TouchGesture::LongPress {
+                        x: touch.x as f32,
+                        y: touch.y as f32,
+                    });
+                }
+            }
+            (Some(first), Some(second)) => {
+                self.multi_touch = true;
+                let pair = FingerPair::new(first, second);
+                gestures.extend(self.two_fingers(pair));
+                self.two_finger_last = Some(pair);
+            }
+        }
+        gestures
+    }
+
+    fn finger_lifted(&mut self, start: Touch, last: Touch) -> Option<TouchGesture> {
+        let (x, y) = (last.x as f32, last.y as f32);
+        if !self.moved && !self.long_pressed && self.held_seconds < self.tap_seconds {
+            let double = self.since_tap <= self.double_tap_seconds
+                && self
+                    .last_tap
+                    .is_some_and(|tap| (tap.0 - x).hypot(tap.1 - y) <= self.tap_slop);
+            if double {
+                self.last_tap = None;
+                return Some(TouchGesture::DoubleTap { x, y });
+            }
+            self.last_tap = Some((x, y));
+            self.since_tap = 0.0;
+            return Some(TouchGesture::Tap { x, y });
+        }
+
+        let travelled = distance(&start, &last);
+        let velocity = travelled / self.held_seconds.max(f32::EPSILON);
+        if self.moved && travelled >= self.swipe_distance && velocity >= self.swipe_velocity {
+            let (dx, dy) = (x - start.x as f32, y - start.y as f32);
+            let direction = if dx.abs() >= dy.abs() {
+                if dx > 0.0 {
+                    SwipeDirection::Right
+                } else {
+                    SwipeDirection::Left
+                }
+            } else if dy > 0.0 {
+                SwipeDirection::Down
+            } else {
+                SwipeDirection::Up
+            };
+            return Some(TouchGesture::Swipe {
+                direction,
+                velocity,
+            });
+        }
+        None
+    }
+
+    fn two_fingers(&mut self, pair: FingerPair) -> Option<TouchGesture> {
+        let (start, mut last) = match (self.two_finger_start, self.two_finger_last) {
+            (Some(start), Some(last)) if start.ids == pair.ids => (start, last),
+            _ => {
+                self.two_finger_start = Some(pair);
+                self.mode = TwoFingerMode::Undecided;
+                return None;
+            }
+        };
+
+        if self.mode == TwoFingerMode::Undecided {
+            // the first gesture covers the whole movement since the fingers touched
+            last = start;
+            let moved = (pair.center.0 - start.center.0).hypot(pair.center.1 - start.center.1);
+            let scaled = (pair.distance / start.distance.max(f32::EPSILON) - 1.0).abs();
+            let rotated = angle_difference(pair.angle, start.angle).abs();
+            self.mode = if scaled >= self.pinch_ratio {
+                TwoFingerMode::Pinch
+            } else if rotated >= self.rotate_degrees {
+                TwoFingerMode::Rotate
+            } else if moved >= self.scroll_distance {
+                TwoFingerMode::Scroll
+            } else {
+                TwoFingerMode::Undecided
+            };
+        }
+
+        match self.mode {
+            TwoFingerMode::Undecided => None,
+            TwoFingerMode::Scroll => Some(TouchGesture::Scroll {
+                dx: pair.center.0 - last.center.0,
+                dy: pair.center.1 - last.center.1,
+            }),
+            TwoFingerMode::Pinch => Some(TouchGesture::Pinch {
+                scale: pair.distance / last.distance.max(f32::EPSILON),
+            }),
+            TwoFingerMode::Rotate => Some(TouchGesture::Rotate {
+                degrees: angle_difference(pair.angle, last.angle),
+            }),
+        }
+    }
+
+    fn reset_contact(&mut self) {
+        self.start = None;
+        self.last = None;
+        self.held_seconds = 0.0;
+        self.moved = false;
+        self.long_pressed = false;
+        self.multi_touch = false;
+        self.mode = TwoFingerMode::Undecided;
+        self.two_finger_start = None;
+        self.two_finger_last = None;
+    }
+}
+
+impl Default for TouchGestureRecognizer {
+    /// Taps under 0.25 seconds and 40 units, double taps within 0.3 seconds, long presses after 0.6 seconds,
+    /// swipes over 300 units at 1000 units/second, scrolls after 40 units, pinches after 10% and rotations after
+    /// 15 degrees
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Position of two fingers relative to each other
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FingerPair {
+    ids: (u8, u8),
+    center: (f32, f32),
+    distance: f32,
+    /// Clockwise, in degrees
+    angle: f32,
+}
+
+impl FingerPair {
+    fn new(first: &Touch, second: &Touch) -> Self {
+        let (dx, dy) = (
+            second.x as f32 - first.x as f32,
+            second.y as f32 - first.y as f32,
+        );
+        Self {
+            ids: (first.id, second.id),
+            center: (
+                (first.x as f32 + second.x as f32) / 2.0,
+                (first.y as f32 + second.y as f32) / 2.0,
+            ),
+            distance: dx.hypot(dy),
+            angle: dy.atan2(dx).to_degrees(),
+        }
+    }
+}
+
+fn distance(from: &Touch, to: &Touch) -> f32 {
+    (to.x as f32 - from.x as f32).hypot(to.y as f32 - from.y as f32)
+}
+
+/// Difference between two angles in the (-180, 180] interval, in degrees
+fn angle_difference(to: f32, from: f32) -> f32 {
+    let difference = (to - from).rem_euclid(360.0);
+    if difference > 180.0 {
+        difference - 360.0
+    } else {
+        difference
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seconds between two frames
+    const DT: f32 = 0.01;
+
+    fn finger(id: u8, x: u16, y: u16) -> Touch {
+        Touch {
+            id,
+            active: true,
+            x,
+            y,
+        }
+    }
+
+    fn frame(touches: &[Touch]) -> TouchFrame {
+        let mut frame = TouchFrame::default();
+        frame.touches[..touches.len()].copy_from_slice(touches);
+        frame
+    }
+
+    fn lifted() -> TouchFrame {
+        TouchFrame::default()
+    }
+
+    /// Finger `id` held still for `frames` frames, then lifted
+    fn press(id: u8, x: u16, y: u16, frames: usize) -> Vec<TouchFrame> {
+        let mut frames = vec![frame(&[finger(id, x, y)]); frames];
+        frames.push(lifted());
+        frames
+    }
+
+    /// Finger `id` moving in a straight line over `frames` frames, then lifted
+    fn drag(id: u8, from: (u16, u16), to: (u16, u16), frames: usize) -> Vec<TouchFrame> {
+        let lerp = |from: u16, to: u16, i: usize| {
+            (from as f32 + (to as f32 - from as f32) * i as f32 / frames as f32).round() as u16
+        };
+        let mut frames = (0..=frames)
+            .map(|i| frame(&[finger(id, lerp(from.0, to.0, i), lerp(from.1, to.1, i))]))
+            .collect::<Vec<_>>();
+        frames.push(lifted());
+        frames
+    }
+
+    fn run(recognizer: &mut TouchGestureRecognizer, frames: &[TouchFrame]) -> Vec<TouchGesture> {
+        frames
+            .iter()
+            .flat_map(|frame| recognizer.update(*frame, DT))
+            .collect()
+    }
+
+    #[test]
+    fn tap() {
+        let mut recognizer = TouchGestureRecognizer::default();
+        assert_eq!(
+            run(&mut recognizer, &press(1, 500, 400, 5)),
+            [TouchGesture::Tap { x: 500.0, y: 400.0 }]
+        );
+        // too long to be a tap
+        assert_eq!(run(&mut recognizer, &press(2, 500, 400, 40)), []);
+    }
+
+    #[test]
+    fn double_tap() {
+        let mut recognizer = TouchGestureRecognizer::default();
+        let mut frames = press(1, 500, 400, 5);
+        frames.extend(vec![lifted(); 10]);
+        frames.extend(press(2, 510, 405, 5));
+        assert_eq!(
+            run(&mut recognizer, &frames),
+            [
+                TouchGesture::Tap { x: 500.0, y: 400.0 },
+                TouchGesture::DoubleTap { x: 510.0, y: 405.0 }
+            ]
+        );
+
+        // too late, then too far
+        let mut frames = press(3, 500, 400, 5);
+        frames.extend(vec![lifted(); 40]);
+        frames.extend(press(4, 500, 400, 5));
+        frames.extend(press(5, 900, 400, 5));
+        assert_eq!(
+            run(&mut recognizer, &frames),
+            [
+                TouchGesture::Tap { x: 500.0, y: 400.0 },
+                TouchGesture::Tap { x: 500.0, y: 400.0 },
+                TouchGesture::Tap { x: 900.0, y: 400.0 }
+            ]
+        );
+    }
+
+    #[test]
+    fn swipe() {
+        let mut recognizer = TouchGestureRecognizer::default();
+        let gestures = run(&mut recognizer, &drag(1, (200, 500), (1400, 500), 20));
+        let [TouchGesture::Swipe {
+            direction,
+            velocity,
+        }] = gestures[..]
+        else {
+            panic!("{gestures:?}");
+        };
+        assert_eq!(direction, SwipeDirection::Right);
+        assert!((velocity - 6000.0).abs() < 1.0, "{velocity}");
+
+        let gestures = run(&mut recognizer, &drag(2, (900, 900), (950, 100), 20));
+        assert!(matches!(
+            gestures[..],
+            [TouchGesture::Swipe {
+                direction: SwipeDirection::Up,
+                ..
+            }]
+        ));
+
+        // too slow, and too short
+        assert_eq!(
+            run(&mut recognizer, &drag(3, (200, 500), (1400, 500), 200)),
+            []
+        );
+        assert_eq!(
+            run(&mut recognizer, &drag(4, (200, 500), (400, 500), 5)),
+            []
+        );
+    }
+
+    #[test]
+    fn long_press() {
+        let mut recognizer = TouchGestureRecognizer::default();
+        let frames = press(1, 700, 300, 100);
+        let gestures = recognizer.update(frames[0], DT);
+        assert_eq!(gestures, []);
+        let held = frames[1..]
+            .iter()
+            .position(|frame| !recognizer.update(*frame, DT).is_empty())
+            .unwrap();
+        assert!((59..=61).contains(&held), "long press after {held} frames");
+        // reported once, and the release is not a tap
+        assert_eq!(run(&mut recognizer, &frames[held + 2..]), []);
+
+        // moving cancels the long press
+        assert_eq!(
+            run(&mut recognizer, &drag(2, (700, 300), (1000, 300), 100)),
+            []
+        );
+    }
+
+    #[test]
+    fn pinch() {
+        let mut recognizer = TouchGestureRecognizer::default();
+        let mut frames = (0..=10)
+            .map(|i| frame(&[finger(1, 800 - 10 * i, 500), finger(2, 1000 + 10 * i, 500)]))
+            .collect::<Vec<_>>();
+        frames.push(lifted());
+        let gestures = run(&mut recognizer, &frames);
+        assert_eq!(gestures.len(), 10);
+        let scale = gestures.iter().fold(1.0, |total, gesture| match gesture {
+            TouchGesture::Pinch { scale } => total * scale,
+            _ => panic!("{gesture:?}"),
+        });
+        assert!((scale - 2.0).abs() < 1e-4, "{scale}");
+        assert_eq!(gestures[0], TouchGesture::Pinch { scale: 1.1 });
+    }
+
+    #[test]
+    fn scroll() {
+        let mut recognizer = TouchGestureRecognizer::default();
+        let frames = (0..=10)
+            .map(|i| frame(&[finger(1, 800, 500 + 10 * i), finger(2, 1000, 500 + 10 * i)]))
+            .collect::<Vec<_>>();
+        let gestures = run(&mut recognizer, &frames);
+        // undecided until the fingers moved 40 units together, then the whole movement is reported
+        assert_eq!(gestures.len(), 7);
+        assert_eq!(gestures[0], TouchGesture::Scroll { dx: 0.0, dy: 40.0 });
+        assert!(gestures[1..]
+            .iter()
+            .all(|gesture| *gesture == TouchGesture::Scroll { dx: 0.0, dy: 10.0 }));
+    }
+
+    #[test]
+    fn rotate() {
+        let mut recognizer = TouchGestureRecognizer::default();
+        // both fingers 200 units from the center, turning 5 degrees clockwise each frame
+        let mut frames = (0..=12_u16)
+            .map(|i| {
+                let (sin, cos) = (5.0 * i as f32).to_radians().sin_cos();
+                let (dx, dy) = (200.0 * cos, 200.0 * sin);
+                frame(&[
+                    finger(1, (960.0 - dx).round() as u16, (540.0 - dy).round() as u16),
+                    finger(2, (960.0 + dx).round() as u16, (540.0 + dy).round() as u16),
+                ])
+            })
+            .collect::<Vec<_>>();
+        frames.push(lifted());
+        let gestures = run(&mut recognizer, &frames);
+        let degrees = gestures
+            .iter()
+            .map(|gesture| match gesture {
+                TouchGesture::Rotate { degrees } => *degrees,
+                _ => panic!("{gesture:?}"),
+            })
+            .collect::<Vec<_>>();
+        // undecided until the fingers turned 15 degrees
+        assert_eq!(degrees.len(), 10);
+        assert!((degrees[0] - 15.0).abs() < 0.5, "{degrees:?}");
+        assert!(degrees[1..].iter().all(|step| (step - 5.0).abs() < 0.5));
+        assert!((degrees.iter().sum::<f32>() - 60.0).abs() < 0.5);
+    }
+}
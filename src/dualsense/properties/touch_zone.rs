@@ -0,0 +1,264 @@
+use std::{error::Error, fmt};
+
+use super::{
+    property::ComboProperty,
+    touch::{Touch, TouchFrame},
+};
+
+/// Rectangle of the touchpad acting as a button, in normalized coordinates from the top left corner
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TouchZone {
+    pub id: u8,
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl TouchZone {
+    pub fn new(id: u8, left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self {
+            id,
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// The right and bottom edges are excluded, so that zones sharing an edge do not both contain a finger on it,
+    /// unless they lie on the edge of the touchpad
+    pub fn contains(&self, touch: &Touch) -> bool {
+        let (x, y) = touch.normalize();
+        Self::within(x, self.left, self.right) && Self::within(y, self.top, self.bottom)
+    }
+
+    fn within(value: f32, start: f32, end: f32) -> bool {
+        value >= start && (value < end || (end >= 1.0 && value <= end))
+    }
+}
+
+/// State of a touchpad zone used as a button
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ZoneButton {
+    pub id: u8,
+    pub pressed: bool,
+}
+
+impl From<ComboProperty> for ZoneButton {
+    fn from(value: ComboProperty) -> Self {
+        match value {
+            ComboProperty::TouchZone(v) => v,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Number of distinct zone ids
+pub const MAX_TOUCH_ZONES: usize = u8::MAX as usize + 1;
+
+/// Splits the touchpad into zones acting as buttons. A zone is pressed while a finger touches it, or, when a click
+/// is required, while the touchpad is pressed down with a finger on the zone. Zones may overlap
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TouchZones {
+    zones: Vec<TouchZone>,
+    require_click: bool,
+}
+
+impl TouchZones {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Left half with id 0 and right half with id 1
+    pub fn halves() -> Self {
+        Self::new()
+            .zone(TouchZone::new(0, 0.0, 0.0, 0.5, 1.0))
+            .zone(TouchZone::new(1, 0.5, 0.0, 1.0, 1.0))
+    }
+
+    /// Equally sized zones, numbered from 0 left to right and then top to bottom. Zone ids are bytes, so there
+    /// can be at most `MAX_TOUCH_ZONES` of them
+    pub fn grid(columns: u8, rows: u8) -> Result<Self, TouchZonesError> {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        if columns as usize * rows as usize > MAX_TOUCH_ZONES {
+            return Err(TouchZonesError::TooManyZones { columns, rows });
+        }
+        let (width, height) = (1.0 / columns as f32, 1.0 / rows as f32);
+        let mut zones = Self::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                zones = zones.zone(TouchZone::new(
+                    (row as usize * columns as usize + column as usize) as u8,
+                    column as f32 * width,
+                    row as f32 * height,
+                    (column + 1) as f32 * width,
+                    (row + 1) as f32 * height,
+                ));
+            }
+        }
+        Ok(zones)
+    }
+
+    pub fn zone(mut self, zone: TouchZone) -> Self {
+        self.zones.push(zone);
+        self
+    }
+
+    /// Only press zones while the touchpad is pressed down
+    pub fn require_click(mut self, require_click: bool) -> Self {
+        self.require_click = require_click;
+        self
+    }
+
+    pub fn zones(&self) -> &[TouchZone] {
+        &self.zones
+    }
+
+    /// Whether the zone with the given id is pressed, `clicked` being whether the touchpad is pressed down
+    pub fn is_pressed(&self, id: u8, frame: &TouchFrame, clicked: bool) -> bool {
+        if self.require_click && !clicked {
+            return false;
+        }
+        self.zones
+            .iter()
+            .filter(|zone| zone.id == id)
+            .any(|zone| frame.active().any(|touch| zone.contains(touch)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TouchZonesError {
+    /// The grid has more zones than there are ids
+    TooManyZones { columns: u8, rows: u8 },
+}
+
+impl fmt::Display for TouchZonesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TouchZonesError::TooManyZones { columns, rows } => write!(
+                f,
+                "{columns}x{rows} touch zones, expected at most {MAX_TOUCH_ZONES}"
+            ),
+        }
+    }
+}
+
+impl Error for TouchZonesError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_ids_are_unique() {
+        let zones = TouchZones::grid(16, 16).unwrap();
+        let mut ids = zones.zones().iter().map(|zone| zone.id).collect::<Vec<_>>();
+        ids.dedup();
+        assert_eq!(ids, (0..=u8::MAX).collect::<Vec<_>>());
+        assert_eq!(
+            zones.zones()[17],
+            TouchZone::new(17, 0.0625, 0.0625, 0.125, 0.125)
+        );
+    }
+
+    #[test]
+    fn grid_with_too_many_zones() {
+        assert_eq!(
+            TouchZones::grid(255, 2),
+            Err(TouchZonesError::TooManyZones {
+                columns: 255,
+                rows: 2
+            })
+        );
+        assert_eq!(TouchZones::grid(0, 0).unwrap().zones().len(), 1);
+        assert_eq!(TouchZones::grid(2, 1).unwrap(), TouchZones::halves());
+    }
+
+    fn finger(id: u8, x: u16, y: u16) -> Touch {
+        Touch {
+            id,
+            active: true,
+            x,
+            y,
+        }
+    }
+
+    fn frame(touches: &[Touch]) -> TouchFrame {
+        let mut frame = TouchFrame::default();
+        frame.touches[..touches.len()].copy_from_slice(touches);
+        frame
+    }
+
+    fn pressed(zones: &TouchZones, frame: &TouchFrame, clicked: bool) -> Vec<u8> {
+        zones
+            .zones()
+            .iter()
+            .map(|zone| zone.id)
+            .filter(|id| zones.is_pressed(*id, frame, clicked))
+            .collect()
+    }
+
+    #[test]
+    fn shared_edge_belongs_to_one_zone() {
+        // the normalized x of a finger at 101 is exactly on the edge
+        let edge = 101.0 / 1919.0;
+        let left = TouchZone::new(0, 0.0, 0.0, edge, 1.0);
+        let right = TouchZone::new(1, edge, 0.0, 1.0, 1.0);
+        assert!(!left.contains(&finger(1, 101, 500)));
+        assert!(right.contains(&finger(1, 101, 500)));
+        assert!(left.contains(&finger(1, 100, 500)));
+
+        let halves = TouchZones::halves();
+        assert_eq!(pressed(&halves, &frame(&[finger(1, 959, 500)]), false), [0]);
+        assert_eq!(pressed(&halves, &frame(&[finger(1, 960, 500)]), false), [1]);
+    }
+
+    #[test]
+    fn touchpad_edges_are_included() {
+        let zones = TouchZones::grid(2, 2).unwrap();
+        let corners = [
+            ((0, 0), 0),
+            ((1919, 0), 1),
+            ((0, 1079), 2),
+            ((1919, 1079), 3),
+        ];
+        for ((x, y), id) in corners {
+            assert_eq!(pressed(&zones, &frame(&[finger(1, x, y)]), false), [id]);
+        }
+        // past the touchpad resolution
+        assert_eq!(
+            pressed(&zones, &frame(&[finger(1, 4095, 4095)]), false),
+            [3]
+        );
+    }
+
+    #[test]
+    fn is_pressed() {
+        let zones = TouchZones::grid(2, 1)
+            .unwrap()
+            .zone(TouchZone::new(5, 0.25, 0.0, 0.75, 0.5));
+        assert_eq!(
+            pressed(&zones, &TouchFrame::default(), true),
+            Vec::<u8>::new()
+        );
+        assert_eq!(pressed(&zones, &frame(&[finger(1, 100, 800)]), false), [0]);
+        // overlapping zones, and both fingers
+        assert_eq!(
+            pressed(&zones, &frame(&[finger(1, 700, 100)]), false),
+            [0, 5]
+        );
+        let both = frame(&[finger(1, 100, 800), finger(2, 1800, 800)]);
+        assert_eq!(pressed(&zones, &both, false), [0, 1]);
+        // lifted fingers are ignored
+        let mut lifted = both;
+        lifted.touches[1].active = false;
+        assert_eq!(pressed(&zones, &lifted, false), [0]);
+        // unknown id
+        assert!(!zones.is_pressed(9, &both, true));
+
+        let zones = zones.require_click(true);
+        assert_eq!(pressed(&zones, &both, false), Vec::<u8>::new());
+        assert_eq!(pressed(&zones, &both, true), [0, 1]);
+    }
+}
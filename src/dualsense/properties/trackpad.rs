@@ -0,0 +1,304 @@
+use super::{
+    touch::{Touch, TouchFrame, TOUCHPAD_WIDTH},
+    touch_gesture::{TouchGesture, TouchGestureRecognizer},
+};
+
+/// Finger speed, in touchpad units/second, at which the sensitivity is multiplied by `1 + acceleration`
+const ACCELERATION_REFERENCE_SPEED: f32 = 2000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+}
+
+/// Mouse input produced from the touchpad
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MouseEvent {
+    /// Relative cursor movement, in pixels
+    Move {
+        dx: f32,
+        dy: f32,
+    },
+    Press(MouseButton),
+    Release(MouseButton),
+    /// Scroll in the direction the fingers moved, in pixels
+    Scroll {
+        dx: f32,
+        dy: f32,
+    },
+}
+
+/// Turns the touchpad into a laptop style trackpad: one finger moves the cursor, tapping or pressing the touchpad
+/// clicks and two fingers scroll. Pressing the touchpad right of the split gives a right click
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackpadMapper {
+    sensitivity: f32,
+    acceleration: f32,
+    max_sensitivity: f32,
+    scroll_sensitivity: f32,
+    tap_to_click: bool,
+    right_click_split: f32,
+    gestures: TouchGestureRecognizer,
+    last: Option<Touch>,
+    pressed: Option<MouseButton>,
+    clicked_during_contact: bool,
+}
+
+impl TrackpadMapper {
+    /// `sensitivity`: cursor pixels for each touchpad unit
+    pub fn new(sensitivity: f32) -> Self {
+        Self {
+            sensitivity,
+            acceleration: 0.0,
+            max_sensitivity: sensitivity,
+            scroll_sensitivity: sensitivity,
+            tap_to_click: true,
+            right_click_split: 0.5,
+            gestures: TouchGestureRecognizer::default(),
+            last: None,
+            pressed: None,
+            clicked_during_contact: false,
+        }
+    }
+
+    /// Raise the sensitivity with the finger speed: at 2000 units/second it is multiplied by `1 + acceleration`,
+    /// up to `max_sensitivity`
+    pub fn acceleration(mut self, acceleration: f32, max_sensitivity: f32) -> Self {
+        self.acceleration = acceleration.max(0.0);
+        self.max_sensitivity = max_sensitivity;
+        self
+    }
+
+    /// Scroll pixels for each touchpad unit the two fingers move
+    pub fn scroll_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.scroll_sensitivity = sensitivity;
+        self
+    }
+
+    pub fn tap_to_click(mut self, enabled: bool) -> Self {
+        self.tap_to_click = enabled;
+        self
+    }
+
+    /// Horizontal position, in the [0, 1] interval, right of which pressing the touchpad gives a right click. 1
+    /// to always give a left click
+    pub fn right_click_split(mut self, split: f32) -> Self {
+        self.right_click_split = split;
+        self
+    }
+
+    /// Thresholds used to recognize taps and two finger scrolling
+    pub fn gestures(mut self, gestures: TouchGestureRecognizer) -> Self {
+        self.gestures = gestures;
+        self
+    }
+
+    /// Feed the next touch frame, whether the touchpad is pressed down and the seconds elapsed since the previous
+    /// update
+    pub fn update(&mut self, frame: TouchFrame, clicked: bool, dt: f32) -> Vec<MouseEvent> {
+        let mut events = Vec::new();
+
+        let mut active = frame.active();
+        let single = match (active.next(), active.next()) {
+            (Some(touch), None) => Some(*touch),
+            _ => None,
+        };
+        if let (Some(touch), Some(last)) = (single, self.last) {
+            if touch.id == last.id {
+                let dx = touch.x as f32 - last.x as f32;
+                let dy = touch.y as f32 - last.y as f32;
+                if dx != 0.0 || dy != 0.0 {
+                    let sensitivity = self.sensitivity_at(dx.hypot(dy) / dt.max(f32::EPSILON));
+                    events.push(MouseEvent::Move {
+                        dx: dx * sensitivity,
+                        dy: dy * sensitivity,
+                    });
+                }
+            }
+        }
+        self.last = single;
+
+        match (clicked, self.pressed) {
+            (true, None) => {
+                let x = frame.active().next().map(|touch| touch.x).unwrap_or(0);
+                let button = if x as f32 / (TOUCHPAD_WIDTH - 1) as f32 > self.right_click_split {
+                    MouseButton::Right
+                } else {
+                    MouseButton::Left
+                };
+                self.pressed = Some(button);
+                self.clicked_during_contact = true;
+                events.push(MouseEvent::Press(button));
+            }
+            (false, Some(button)) => {
+                self.pressed = None;
+                events.push(MouseEvent::Release(button));
+            }
+            _ => {}
+        }
+
+        for gesture in self.gestures.update(frame, dt) {
+            match gesture {
+                TouchGesture::Tap { .. } | TouchGesture::DoubleTap { .. }
+                    if self.tap_to_click && !self.clicked_during_contact =>
+                {
+                    events.push(MouseEvent::Press(MouseButton::Left));
+                    events.push(MouseEvent::Release(MouseButton::Left));
+                }
+                TouchGesture::Scroll { dx, dy } => events.push(MouseEvent::Scroll {
+                    dx: dx * self.scroll_sensitivity,
+                    dy: dy * self.scroll_sensitivity,
+                }),
+                _ => {}
+            }
+        }
+        if frame.active().next().is_none() && self.pressed.is_none() {
+            self.clicked_during_contact = false;
+        }
+        events
+    }
+
+    fn sensitivity_at(&self, speed: f32) -> f32 {
+        let accelerated =
+            self.sensitivity * (1.0 + self.acceleration * speed / ACCELERATION_REFERENCE_SPEED);
+        accelerated.min(self.max_sensitivity.max(self.sensitivity))
+    }
+}
+
+impl Default for TrackpadMapper {
+    /// One pixel per touchpad unit, no acceleration, tap to click and right click on the right half
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seconds between two frames
+    const DT: f32 = 0.01;
+
+    fn finger(id: u8, x: u16, y: u16) -> Touch {
+        Touch {
+            id,
+            active: true,
+            x,
+            y,
+        }
+    }
+
+    fn frame(touches: &[Touch]) -> TouchFrame {
+        let mut frame = TouchFrame::default();
+        frame.touches[..touches.len()].copy_from_slice(touches);
+        frame
+    }
+
+    fn moves(events: &[MouseEvent]) -> Vec<(f32, f32)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                MouseEvent::Move { dx, dy } => Some((*dx, *dy)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finger_moves_the_cursor() {
+        let mut trackpad = TrackpadMapper::new(0.5);
+        assert_eq!(
+            trackpad.update(frame(&[finger(1, 100, 100)]), false, DT),
+            []
+        );
+        assert_eq!(
+            trackpad.update(frame(&[finger(1, 110, 94)]), false, DT),
+            [MouseEvent::Move { dx: 5.0, dy: -3.0 }]
+        );
+        // no movement, then a new finger does not jump the cursor
+        assert_eq!(trackpad.update(frame(&[finger(1, 110, 94)]), false, DT), []);
+        assert_eq!(
+            trackpad.update(frame(&[finger(2, 900, 900)]), false, DT),
+            []
+        );
+    }
+
+    #[test]
+    fn acceleration() {
+        let mut trackpad = TrackpadMapper::new(1.0).acceleration(1.0, 1.5);
+        trackpad.update(frame(&[finger(1, 100, 100)]), false, DT);
+        // 5 units in 10 ms is 500 units/second, a quarter of the reference speed
+        let events = trackpad.update(frame(&[finger(1, 105, 100)]), false, DT);
+        assert_eq!(moves(&events), [(6.25, 0.0)]);
+        // 40 units in 10 ms is capped to the maximum sensitivity
+        let events = trackpad.update(frame(&[finger(1, 145, 100)]), false, DT);
+        assert_eq!(moves(&events), [(60.0, 0.0)]);
+    }
+
+    #[test]
+    fn click_left_and_right() {
+        let mut trackpad = TrackpadMapper::default();
+        let left = frame(&[finger(1, 400, 500)]);
+        assert_eq!(trackpad.update(left, false, DT), []);
+        assert_eq!(
+            trackpad.update(left, true, DT),
+            [MouseEvent::Press(MouseButton::Left)]
+        );
+        assert_eq!(trackpad.update(left, true, DT), []);
+        assert_eq!(
+            trackpad.update(left, false, DT),
+            [MouseEvent::Release(MouseButton::Left)]
+        );
+        // the short contact was a click, not a tap as well
+        assert_eq!(trackpad.update(TouchFrame::default(), false, DT), []);
+
+        let right = frame(&[finger(2, 1500, 500)]);
+        trackpad.update(right, false, DT);
+        assert_eq!(
+            trackpad.update(right, true, DT),
+            [MouseEvent::Press(MouseButton::Right)]
+        );
+        // the release matches the press
+        assert_eq!(
+            trackpad.update(frame(&[finger(2, 1500, 500)]), false, DT),
+            [MouseEvent::Release(MouseButton::Right)]
+        );
+    }
+
+    #[test]
+    fn tap_to_click() {
+        let tap = |trackpad: &mut TrackpadMapper| {
+            let mut events = Vec::new();
+            for _ in 0..5 {
+                events.extend(trackpad.update(frame(&[finger(1, 500, 500)]), false, DT));
+            }
+            events.extend(trackpad.update(TouchFrame::default(), false, DT));
+            events
+        };
+        assert_eq!(
+            tap(&mut TrackpadMapper::default()),
+            [
+                MouseEvent::Press(MouseButton::Left),
+                MouseEvent::Release(MouseButton::Left)
+            ]
+        );
+        assert_eq!(tap(&mut TrackpadMapper::default().tap_to_click(false)), []);
+    }
+
+    #[test]
+    fn two_fingers_scroll() {
+        let mut trackpad = TrackpadMapper::default().scroll_sensitivity(0.5);
+        let events = (0..=10)
+            .flat_map(|i| {
+                let fingers = [finger(1, 800, 500 + 10 * i), finger(2, 1000, 500 + 10 * i)];
+                trackpad.update(frame(&fingers), false, DT)
+            })
+            .collect::<Vec<_>>();
+        assert!(moves(&events).is_empty());
+        assert_eq!(events[0], MouseEvent::Scroll { dx: 0.0, dy: 20.0 });
+        assert!(events[1..]
+            .iter()
+            .all(|event| *event == MouseEvent::Scroll { dx: 0.0, dy: 5.0 }));
+    }
+}
@@ -1,22 +1,192 @@
-use super::traits::Normalizable;
+use std::hash::{Hash, Hasher};
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
-pub struct Trigger(u8);
+use super::{curve::Curve, traits::Normalizable};
+
+/// Position of an analog trigger. Two triggers are equal when their raw values are equal, regardless of the
+/// curve they were configured with
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Trigger {
+    value: u8,
+    curve: Curve,
+}
 
 impl Trigger {
-    pub(crate)fn new(value: u8) -> Self {
-        Self { 0: value }
+    pub(crate) fn new(value: u8) -> Self {
+        Self {
+            value,
+            curve: Curve::default(),
+        }
+    }
+
+    pub(crate) fn with_curve(mut self, curve: Curve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Raw value as sent by the controller, 0 being released and 255 being fully pulled
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+
+    /// Curve applied by `Self::normalize`
+    pub fn curve(&self) -> Curve {
+        self.curve
+    }
+
+    /// Normalize the value to the [0, 1] interval, ignoring the curve
+    pub fn normalize_raw(&self) -> f32 {
+        self.value as f32 / 255.0
     }
 }
 
 impl Normalizable for Trigger {
+    /// Normalize the value to the [0, 1] interval, with the curve applied
     fn normalize(&self) -> f32 {
-        self.0 as f32 / 255.0
+        self.curve.apply(self.normalize_raw())
     }
 }
 
-impl Default for Trigger {
+impl PartialEq for Trigger {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for Trigger {}
+
+impl Hash for Trigger {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+/// Actuation points used to treat a trigger as a digital button. The trigger is pressed once it is pulled past
+/// `press` and released once it goes back under `release`, both in the [0, 1] interval of the trigger's travel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriggerThreshold {
+    press: f32,
+    release: f32,
+}
+
+impl TriggerThreshold {
+    /// The release point is capped to the press point
+    pub fn new(press: f32, release: f32) -> Self {
+        let press = press.clamp(0.0, 1.0);
+        Self {
+            press,
+            release: release.clamp(0.0, press),
+        }
+    }
+
+    /// Pressed as soon as the trigger moves
+    pub fn hair() -> Self {
+        Self::new(0.05, 0.03)
+    }
+
+    /// Pressed only when the trigger is pulled all the way
+    pub fn full() -> Self {
+        Self::new(0.95, 0.9)
+    }
+
+    pub fn press(&self) -> f32 {
+        self.press
+    }
+
+    pub fn release(&self) -> f32 {
+        self.release
+    }
+}
+
+impl Default for TriggerThreshold {
+    /// Pressed at half of the travel, released under 40%
     fn default() -> Self {
-        Self { 0: 0 }
+        Self::new(0.5, 0.4)
+    }
+}
+
+/// Digital button state of a trigger, keeps the previous state between the release and press points so the
+/// button does not flicker when the trigger is held near an actuation point
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TriggerButton {
+    threshold: TriggerThreshold,
+    pressed: bool,
+}
+
+impl TriggerButton {
+    pub fn new(threshold: TriggerThreshold) -> Self {
+        Self {
+            threshold,
+            pressed: false,
+        }
+    }
+
+    /// Feed a new trigger position, returns whether the button is pressed. Uses the raw trigger travel, the
+    /// curve does not move the actuation points
+    pub fn update(&mut self, trigger: &Trigger) -> bool {
+        let travel = trigger.normalize_raw();
+        if self.pressed {
+            self.pressed = travel >= self.threshold.release;
+        } else {
+            self.pressed = travel >= self.threshold.press;
+        }
+        self.pressed
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    pub fn threshold(&self) -> TriggerThreshold {
+        self.threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presses(button: &mut TriggerButton, values: &[u8]) -> Vec<bool> {
+        values
+            .iter()
+            .map(|&value| button.update(&Trigger::new(value)))
+            .collect()
+    }
+
+    #[test]
+    fn threshold_release_is_capped() {
+        let threshold = TriggerThreshold::new(0.3, 0.6);
+        assert_eq!((threshold.press(), threshold.release()), (0.3, 0.3));
+        let threshold = TriggerThreshold::new(1.5, -1.0);
+        assert_eq!((threshold.press(), threshold.release()), (1.0, 0.0));
+    }
+
+    #[test]
+    fn button_hysteresis() {
+        // pressed from 128 (50%), released under 102 (40%)
+        let mut button = TriggerButton::default();
+        assert_eq!(
+            presses(
+                &mut button,
+                &[0, 110, 127, 128, 115, 103, 102, 101, 120, 127]
+            ),
+            vec![false, false, false, true, true, true, true, false, false, false]
+        );
+        assert!(!button.is_pressed());
+    }
+
+    #[test]
+    fn button_held_near_the_press_point_does_not_flicker() {
+        let mut button = TriggerButton::new(TriggerThreshold::new(0.5, 0.4));
+        let values = [126, 129, 127, 130, 126, 128, 125];
+        let states = presses(&mut button, &values);
+        assert_eq!(states, vec![false, true, true, true, true, true, true]);
+    }
+
+    #[test]
+    fn button_ignores_the_curve() {
+        let mut button = TriggerButton::default();
+        let trigger = Trigger::new(140).with_curve(Curve::Power(4.0));
+        assert!(trigger.normalize() < 0.5);
+        assert!(button.update(&trigger));
     }
 }
@@ -1,29 +1,602 @@
-/// Vibration patterns
-#[derive(Clone, Copy)]
+use std::{error::Error, fmt};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Size of the block describing the effect of one trigger in the output report: the mode and 10 parameters
+pub const TRIGGER_EFFECT_SIZE: usize = 11;
+
+/// Resistance or vibration applied by an adaptive trigger. Positions go from 0, the trigger at rest, to 9, the
+/// trigger fully pressed. Strengths and amplitudes go from 1 to 8 unless noted otherwise
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TriggerEffect {
+    /// No resistance
+    #[default]
     Off,
-    Mode1,
-    Mode2,
-    Mode1_4,
-    Mode2_4,
-    Mode1_20,
-    Mode1_4_20,
-    Mode2_4_20,
-    Calibration,
+    /// Constant resistance from `start` (0-9) to the end of the travel
+    Feedback { start: u8, strength: u8 },
+    /// Resistance between `start` (2-7) and `end` (`start + 1`-8) that gives way past `end`, like a gun trigger
+    Weapon { start: u8, end: u8, strength: u8 },
+    /// Vibration from `start` (0-9) to the end of the travel, `frequency` in Hz (1-255)
+    Vibration {
+        start: u8,
+        amplitude: u8,
+        frequency: u8,
+    },
+    /// Resistance of each of the 10 positions, 0 for none
+    MultiPositionFeedback([u8; 10]),
+    /// Resistance going linearly from `start_strength` at `start` (0-8) to `end_strength` at `end` (`start + 1`-9)
+    /// and kept after `end`
+    SlopeFeedback {
+        start: u8,
+        end: u8,
+        start_strength: u8,
+        end_strength: u8,
+    },
+    /// Vibration amplitude of each of the 10 positions, 0 for none, `frequency` in Hz (1-255)
+    MultiPositionVibration { frequency: u8, amplitudes: [u8; 10] },
+    /// Resistance between `start` (0-8) and `end` (`start + 1`-8) snapping the trigger back with `snap_force`
+    /// (1-8) once released, like a bow string
+    Bow {
+        start: u8,
+        end: u8,
+        strength: u8,
+        snap_force: u8,
+    },
+    /// Two taps repeating `frequency` times a second (1-255) between `start` (0-8) and `end` (`start + 1`-9),
+    /// like a galloping horse. `first_foot` (0-6) and `second_foot` (`first_foot + 1`-7) set when each tap
+    /// happens
+    Galloping {
+        start: u8,
+        end: u8,
+        first_foot: u8,
+        second_foot: u8,
+        frequency: u8,
+    },
+    /// Vibration alternating between `amplitude_a` and `amplitude_b` (0-7) between `start` (0-8) and `end`
+    /// (`start + 1`-9), `frequency` in Hz (1-255) and `period` in tenths of a second
+    Machine {
+        start: u8,
+        end: u8,
+        amplitude_a: u8,
+        amplitude_b: u8,
+        frequency: u8,
+        period: u8,
+    },
+    /// Mode byte and parameters sent as they are
+    Raw { mode: u8, parameters: [u8; 10] },
 }
 
 impl TriggerEffect {
-    pub(crate) fn byte(self) -> u8 {
-        match self {
-            TriggerEffect::Off => 0x00,
-            TriggerEffect::Mode1 => 0x01,
-            TriggerEffect::Mode2 => 0x02,
-            TriggerEffect::Mode1_4 => 0x05,
-            TriggerEffect::Mode2_4 => 0x06,
-            TriggerEffect::Mode1_20 => 0x21,
-            TriggerEffect::Mode1_4_20 => 0x25,
-            TriggerEffect::Mode2_4_20 => 0x26,
-            TriggerEffect::Calibration => 0xFC,
+    /// Encode the effect into the trigger block of the output report
+    pub fn encode(&self) -> Result<[u8; TRIGGER_EFFECT_SIZE], TriggerEffectError> {
+        match *self {
+            TriggerEffect::Off => Ok(block(0x05, &[])),
+            TriggerEffect::Feedback { start, strength } => {
+                check("start", start, 0, 9)?;
+                check("strength", strength, 1, 8)?;
+                let mut strengths = [0; 10];
+                strengths[start as usize..].fill(strength);
+                TriggerEffect::MultiPositionFeedback(strengths).encode()
+            }
+            TriggerEffect::Weapon {
+                start,
+                end,
+                strength,
+            } => {
+                check("start", start, 2, 7)?;
+                check("end", end, start + 1, 8)?;
+                check("strength", strength, 1, 8)?;
+                let zones = zone_pair(start, end);
+                Ok(block(0x25, &[zones[0], zones[1], strength - 1]))
+            }
+            TriggerEffect::Vibration {
+                start,
+                amplitude,
+                frequency,
+            } => {
+                check("start", start, 0, 9)?;
+                check("amplitude", amplitude, 1, 8)?;
+                let mut amplitudes = [0; 10];
+                amplitudes[start as usize..].fill(amplitude);
+                TriggerEffect::MultiPositionVibration {
+                    frequency,
+                    amplitudes,
+                }
+                .encode()
+            }
+            TriggerEffect::MultiPositionFeedback(strengths) => {
+                let (zones, forces) = zone_values(&strengths, "strength")?;
+                Ok(block(
+                    0x21,
+                    &[
+                        zones[0], zones[1], forces[0], forces[1], forces[2], forces[3],
+                    ],
+                ))
+            }
+            TriggerEffect::SlopeFeedback {
+                start,
+                end,
+                start_strength,
+                end_strength,
+            } => {
+                check("start", start, 0, 8)?;
+                check("end", end, start + 1, 9)?;
+                check("start_strength", start_strength, 1, 8)?;
+                check("end_strength", end_strength, 1, 8)?;
+                let slope =
+                    (end_strength as f32 - start_strength as f32) / (end as f32 - start as f32);
+                let mut strengths = [0; 10];
+                for (position, strength) in strengths.iter_mut().enumerate().skip(start as usize) {
+                    *strength = if position <= end as usize {
+                        (start_strength as f32 + slope * (position as f32 - start as f32))
+                            .round_ties_even() as u8
+                    } else {
+                        end_strength
+                    };
+                }
+                TriggerEffect::MultiPositionFeedback(strengths).encode()
+            }
+            TriggerEffect::MultiPositionVibration {
+                frequency,
+                amplitudes,
+            } => {
+                check("frequency", frequency, 1, 255)?;
+                let (zones, forces) = zone_values(&amplitudes, "amplitude")?;
+                Ok(block(
+                    0x26,
+                    &[
+                        zones[0], zones[1], forces[0], forces[1], forces[2], forces[3], 0, 0,
+                        frequency,
+                    ],
+                ))
+            }
+            TriggerEffect::Bow {
+                start,
+                end,
+                strength,
+                snap_force,
+            } => {
+                check("start", start, 0, 8)?;
+                check("end", end, start + 1, 8)?;
+                check("strength", strength, 1, 8)?;
+                check("snap_force", snap_force, 1, 8)?;
+                let zones = zone_pair(start, end);
+                let forces = ((strength - 1) & 0x07) | (((snap_force - 1) & 0x07) << 3);
+                Ok(block(0x22, &[zones[0], zones[1], forces]))
+            }
+            TriggerEffect::Galloping {
+                start,
+                end,
+                first_foot,
+                second_foot,
+                frequency,
+            } => {
+                check("start", start, 0, 8)?;
+                check("end", end, start + 1, 9)?;
+                check("first_foot", first_foot, 0, 6)?;
+                check("second_foot", second_foot, first_foot + 1, 7)?;
+                check("frequency", frequency, 1, 255)?;
+                let zones = zone_pair(start, end);
+                let feet = (second_foot & 0x07) | ((first_foot & 0x07) << 3);
+                Ok(block(0x23, &[zones[0], zones[1], feet, frequency]))
+            }
+            TriggerEffect::Machine {
+                start,
+                end,
+                amplitude_a,
+                amplitude_b,
+                frequency,
+                period,
+            } => {
+                check("start", start, 0, 8)?;
+                check("end", end, start + 1, 9)?;
+                check("amplitude_a", amplitude_a, 0, 7)?;
+                check("amplitude_b", amplitude_b, 0, 7)?;
+                check("frequency", frequency, 1, 255)?;
+                let zones = zone_pair(start, end);
+                let amplitudes = (amplitude_a & 0x07) | ((amplitude_b & 0x07) << 3);
+                Ok(block(
+                    0x27,
+                    &[zones[0], zones[1], amplitudes, frequency, period],
+                ))
+            }
+            TriggerEffect::Raw { mode, parameters } => Ok(block(mode, &parameters)),
+        }
+    }
+}
+
+/// Parameter of a `TriggerEffect` outside of its allowed range
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TriggerEffectError {
+    pub parameter: &'static str,
+    pub value: u8,
+    pub min: u8,
+    pub max: u8,
+}
+
+impl fmt::Display for TriggerEffectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid trigger effect {} {}, expected {}-{}",
+            self.parameter, self.value, self.min, self.max
+        )
+    }
+}
+
+impl Error for TriggerEffectError {}
+
+fn check(parameter: &'static str, value: u8, min: u8, max: u8) -> Result<(), TriggerEffectError> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(TriggerEffectError {
+            parameter,
+            value,
+            min,
+            max,
+        })
+    }
+}
+
+fn block(mode: u8, parameters: &[u8]) -> [u8; TRIGGER_EFFECT_SIZE] {
+    let mut block = [0; TRIGGER_EFFECT_SIZE];
+    block[0] = mode;
+    block[1..=parameters.len()].copy_from_slice(parameters);
+    block
+}
+
+/// Bits of the two positions delimiting an effect
+fn zone_pair(start: u8, end: u8) -> [u8; 2] {
+    ((1_u16 << start) | (1_u16 << end)).to_le_bytes()
+}
+
+/// Bits of the positions with a value and the values minus one packed on 3 bits each, 0 meaning no value
+fn zone_values(
+    values: &[u8; 10],
+    parameter: &'static str,
+) -> Result<([u8; 2], [u8; 4]), TriggerEffectError> {
+    let mut zones = 0_u16;
+    let mut packed = 0_u32;
+    for (position, value) in values.iter().enumerate() {
+        check(parameter, *value, 0, 8)?;
+        if *value > 0 {
+            zones |= 1 << position;
+            packed |= ((*value as u32 - 1) & 0x07) << (3 * position);
+        }
+    }
+    Ok((zones.to_le_bytes(), packed.to_le_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(parameter: &'static str, value: u8, min: u8, max: u8) -> TriggerEffectError {
+        TriggerEffectError {
+            parameter,
+            value,
+            min,
+            max,
+        }
+    }
+
+    #[test]
+    fn off() {
+        assert_eq!(
+            TriggerEffect::Off.encode(),
+            Ok([0x05, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn feedback() {
+        assert_eq!(
+            TriggerEffect::Feedback {
+                start: 0,
+                strength: 8
+            }
+            .encode(),
+            Ok([0x21, 0xFF, 0x03, 0xFF, 0xFF, 0xFF, 0x3F, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn multi_position_feedback() {
+        assert_eq!(
+            TriggerEffect::MultiPositionFeedback([1, 0, 0, 0, 0, 0, 0, 0, 0, 8]).encode(),
+            Ok([0x21, 0x01, 0x02, 0, 0, 0, 0x38, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn slope_feedback() {
+        let slope = TriggerEffect::SlopeFeedback {
+            start: 2,
+            end: 4,
+            start_strength: 2,
+            end_strength: 4,
+        }
+        .encode();
+        assert_eq!(
+            slope,
+            Ok([0x21, 0xFC, 0x03, 0x40, 0xB4, 0x6D, 0x1B, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            slope,
+            TriggerEffect::MultiPositionFeedback([0, 0, 2, 3, 4, 4, 4, 4, 4, 4]).encode()
+        );
+    }
+
+    #[test]
+    fn bow() {
+        assert_eq!(
+            TriggerEffect::Bow {
+                start: 1,
+                end: 4,
+                strength: 8,
+                snap_force: 8
+            }
+            .encode(),
+            Ok([0x22, 0x12, 0x00, 0x3F, 0, 0, 0, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn galloping() {
+        assert_eq!(
+            TriggerEffect::Galloping {
+                start: 0,
+                end: 9,
+                first_foot: 2,
+                second_foot: 4,
+                frequency: 2
+            }
+            .encode(),
+            Ok([0x23, 0x01, 0x02, 0x14, 0x02, 0, 0, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn weapon() {
+        assert_eq!(
+            TriggerEffect::Weapon {
+                start: 2,
+                end: 5,
+                strength: 8
+            }
+            .encode(),
+            Ok([0x25, 0x24, 0x00, 0x07, 0, 0, 0, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn vibration() {
+        let vibration = TriggerEffect::Vibration {
+            start: 9,
+            amplitude: 1,
+            frequency: 30,
         }
+        .encode();
+        assert_eq!(vibration, Ok([0x26, 0x00, 0x02, 0, 0, 0, 0, 0, 0, 30, 0]));
+        let mut amplitudes = [0; 10];
+        amplitudes[9] = 1;
+        assert_eq!(
+            vibration,
+            TriggerEffect::MultiPositionVibration {
+                frequency: 30,
+                amplitudes
+            }
+            .encode()
+        );
+    }
+
+    #[test]
+    fn machine() {
+        assert_eq!(
+            TriggerEffect::Machine {
+                start: 1,
+                end: 9,
+                amplitude_a: 1,
+                amplitude_b: 7,
+                frequency: 15,
+                period: 3
+            }
+            .encode(),
+            Ok([0x27, 0x02, 0x02, 0x39, 15, 3, 0, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn raw() {
+        assert_eq!(
+            TriggerEffect::Raw {
+                mode: 0x26,
+                parameters: [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+            }
+            .encode(),
+            Ok([0x26, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10])
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let cases = [
+            (
+                TriggerEffect::Feedback {
+                    start: 10,
+                    strength: 8,
+                },
+                error("start", 10, 0, 9),
+            ),
+            (
+                TriggerEffect::Feedback {
+                    start: 0,
+                    strength: 0,
+                },
+                error("strength", 0, 1, 8),
+            ),
+            (
+                TriggerEffect::Weapon {
+                    start: 1,
+                    end: 5,
+                    strength: 8,
+                },
+                error("start", 1, 2, 7),
+            ),
+            (
+                TriggerEffect::Weapon {
+                    start: 4,
+                    end: 4,
+                    strength: 8,
+                },
+                error("end", 4, 5, 8),
+            ),
+            (
+                TriggerEffect::Weapon {
+                    start: 2,
+                    end: 5,
+                    strength: 9,
+                },
+                error("strength", 9, 1, 8),
+            ),
+            (
+                TriggerEffect::Vibration {
+                    start: 0,
+                    amplitude: 9,
+                    frequency: 30,
+                },
+                error("amplitude", 9, 1, 8),
+            ),
+            (
+                TriggerEffect::Vibration {
+                    start: 0,
+                    amplitude: 8,
+                    frequency: 0,
+                },
+                error("frequency", 0, 1, 255),
+            ),
+            (
+                TriggerEffect::MultiPositionFeedback([0, 0, 0, 9, 0, 0, 0, 0, 0, 0]),
+                error("strength", 9, 0, 8),
+            ),
+            (
+                TriggerEffect::MultiPositionVibration {
+                    frequency: 30,
+                    amplitudes: [0, 0, 0, 0, 0, 0, 0, 0, 0, 9],
+                },
+                error("amplitude", 9, 0, 8),
+            ),
+            (
+                TriggerEffect::SlopeFeedback {
+                    start: 9,
+                    end: 9,
+                    start_strength: 1,
+                    end_strength: 8,
+                },
+                error("start", 9, 0, 8),
+            ),
+            (
+                TriggerEffect::SlopeFeedback {
+                    start: 2,
+                    end: 10,
+                    start_strength: 1,
+                    end_strength: 8,
+                },
+                error("end", 10, 3, 9),
+            ),
+            (
+                TriggerEffect::SlopeFeedback {
+                    start: 2,
+                    end: 9,
+                    start_strength: 1,
+                    end_strength: 0,
+                },
+                error("end_strength", 0, 1, 8),
+            ),
+            (
+                TriggerEffect::Bow {
+                    start: 1,
+                    end: 9,
+                    strength: 8,
+                    snap_force: 8,
+                },
+                error("end", 9, 2, 8),
+            ),
+            (
+                TriggerEffect::Bow {
+                    start: 1,
+                    end: 4,
+                    strength: 8,
+                    snap_force: 9,
+                },
+                error("snap_force", 9, 1, 8),
+            ),
+            (
+                TriggerEffect::Galloping {
+                    start: 0,
+                    end: 9,
+                    first_foot: 7,
+                    second_foot: 7,
+                    frequency: 2,
+                },
+                error("first_foot", 7, 0, 6),
+            ),
+            (
+                TriggerEffect::Galloping {
+                    start: 0,
+                    end: 9,
+                    first_foot: 2,
+                    second_foot: 2,
+                    frequency: 2,
+                },
+                error("second_foot", 2, 3, 7),
+            ),
+            (
+                TriggerEffect::Galloping {
+                    start: 0,
+                    end: 9,
+                    first_foot: 2,
+                    second_foot: 4,
+                    frequency: 0,
+                },
+                error("frequency", 0, 1, 255),
+            ),
+            (
+                TriggerEffect::Machine {
+                    start: 1,
+                    end: 9,
+                    amplitude_a: 1,
+                    amplitude_b: 8,
+                    frequency: 15,
+                    period: 3,
+                },
+                error("amplitude_b", 8, 0, 7),
+            ),
+            (
+                TriggerEffect::Machine {
+                    start: 1,
+                    end: 9,
+                    amplitude_a: 1,
+                    amplitude_b: 7,
+                    frequency: 0,
+                    period: 3,
+                },
+                error("frequency", 0, 1, 255),
+            ),
+        ];
+        for (effect, expected) in cases {
+            assert_eq!(effect.encode(), Err(expected), "{effect:?}");
+        }
+    }
+
+    #[test]
+    fn error_message() {
+        assert_eq!(
+            error("start", 10, 0, 9).to_string(),
+            "invalid trigger effect start 10, expected 0-9"
+        );
     }
 }
@@ -0,0 +1,87 @@
+/// Byte of the input report with the right trigger status, the left trigger status follows
+const RIGHT_STATUS_BYTE: usize = 42;
+const LEFT_STATUS_BYTE: usize = 43;
+/// Byte of the input report with the effect running on each trigger, right in the low nibble
+const ACTIVE_EFFECTS_BYTE: usize = 48;
+
+/// Feedback of an adaptive trigger read from the input report: whether the controller runs an effect on it, the
+/// state of that effect and the position where the trigger is held back
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TriggerFeedbackStatus {
+    pub effect_active: bool,
+    /// Raw state reported by the effect, e.g. whether a `TriggerEffect::Weapon` trigger went past its click
+    pub state_nibble: u8,
+    /// Position where the effect stopped the trigger, from 0 at rest to 9 fully pressed
+    pub stop_location: u8,
+}
+
+impl TriggerFeedbackStatus {
+    /// Decode the status byte of a trigger, stop location in the low nibble and state in the high one, and the
+    /// nibble of the effect running on it
+    fn new(status: u8, effect: u8) -> Self {
+        Self {
+            effect_active: effect != 0,
+            state_nibble: status >> 4,
+            stop_location: status & 0x0F,
+        }
+    }
+
+    pub(crate) fn left(data: &[u8]) -> Self {
+        Self::new(data[LEFT_STATUS_BYTE], data[ACTIVE_EFFECTS_BYTE] >> 4)
+    }
+
+    pub(crate) fn right(data: &[u8]) -> Self {
+        Self::new(data[RIGHT_STATUS_BYTE], data[ACTIVE_EFFECTS_BYTE] & 0x0F)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(right_status: u8, left_status: u8, effects: u8) -> [u8; 64] {
+        let mut data = [0; 64];
+        data[42] = right_status;
+        data[43] = left_status;
+        data[48] = effects;
+        data
+    }
+
+    #[test]
+    fn status_nibbles() {
+        let data = report(0x27, 0x93, 0x00);
+        assert_eq!(
+            TriggerFeedbackStatus::right(&data),
+            TriggerFeedbackStatus {
+                effect_active: false,
+                state_nibble: 0x2,
+                stop_location: 7,
+            }
+        );
+        assert_eq!(
+            TriggerFeedbackStatus::left(&data),
+            TriggerFeedbackStatus {
+                effect_active: false,
+                state_nibble: 0x9,
+                stop_location: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn effect_nibbles() {
+        // left in the high nibble, right in the low one
+        let active = |effects| {
+            let data = report(0, 0, effects);
+            (
+                TriggerFeedbackStatus::left(&data).effect_active,
+                TriggerFeedbackStatus::right(&data).effect_active,
+            )
+        };
+        assert_eq!(active(0x00), (false, false));
+        assert_eq!(active(0x20), (true, false));
+        assert_eq!(active(0x02), (false, true));
+        assert_eq!(active(0x81), (true, true));
+        assert_eq!(active(0xF0), (true, false));
+    }
+}
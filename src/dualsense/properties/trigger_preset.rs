@@ -0,0 +1,286 @@
+#[cfg(feature = "serde")]
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+use super::trigger_effect::{TriggerEffect, TriggerEffectError};
+
+/// Trigger effect with a name, so feels can be tuned in files and picked by name
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TriggerPreset {
+    pub name: String,
+    pub effect: TriggerEffect,
+}
+
+impl TriggerPreset {
+    pub fn new(name: &str, effect: TriggerEffect) -> Self {
+        Self {
+            name: name.to_owned(),
+            effect,
+        }
+    }
+
+    /// Resistance giving way halfway through the travel, like a semi-automatic gun
+    pub fn gun() -> Self {
+        Self::new(
+            "gun",
+            TriggerEffect::Weapon {
+                start: 2,
+                end: 5,
+                strength: 8,
+            },
+        )
+    }
+
+    /// Strong vibration once the trigger is slightly pressed, like the recoil of an automatic gun
+    pub fn automatic_gun() -> Self {
+        Self::new(
+            "automatic_gun",
+            TriggerEffect::Vibration {
+                start: 2,
+                amplitude: 8,
+                frequency: 12,
+            },
+        )
+    }
+
+    /// Tension building up while drawing a bow and snapping back once released
+    pub fn bow() -> Self {
+        Self::new(
+            "bow",
+            TriggerEffect::Bow {
+                start: 1,
+                end: 7,
+                strength: 6,
+                snap_force: 8,
+            },
+        )
+    }
+
+    /// Resistance growing with the travel, like a car pedal
+    pub fn accelerator() -> Self {
+        Self::new(
+            "accelerator",
+            TriggerEffect::SlopeFeedback {
+                start: 0,
+                end: 9,
+                start_strength: 1,
+                end_strength: 5,
+            },
+        )
+    }
+
+    /// Firm resistance once the pads touch the disc
+    pub fn brake() -> Self {
+        Self::new(
+            "brake",
+            TriggerEffect::Feedback {
+                start: 3,
+                strength: 7,
+            },
+        )
+    }
+
+    /// Pulsing resistance of an anti-lock braking system
+    pub fn brake_abs() -> Self {
+        Self::new(
+            "brake_abs",
+            TriggerEffect::Machine {
+                start: 3,
+                end: 9,
+                amplitude_a: 1,
+                amplitude_b: 7,
+                frequency: 15,
+                period: 1,
+            },
+        )
+    }
+
+    /// Taps following the rhythm of a galloping horse
+    pub fn horse() -> Self {
+        Self::new(
+            "horse",
+            TriggerEffect::Galloping {
+                start: 0,
+                end: 9,
+                first_foot: 2,
+                second_foot: 4,
+                frequency: 2,
+            },
+        )
+    }
+
+    /// Check that the effect can be sent to the controller
+    pub fn validate(&self) -> Result<(), TriggerEffectError> {
+        self.effect.encode().map(|_| ())
+    }
+}
+
+/// Named trigger effects, loaded from or saved to any format supported by serde when the `serde` feature is
+/// enabled. Names are unique, inserting a preset replaces the one with the same name and loading a file with
+/// the same name twice fails
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TriggerPresets {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "unique_names"))]
+    presets: Vec<TriggerPreset>,
+}
+
+#[cfg(feature = "serde")]
+fn unique_names<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<TriggerPreset>, D::Error> {
+    let presets = Vec::<TriggerPreset>::deserialize(deserializer)?;
+    for (index, preset) in presets.iter().enumerate() {
+        if presets[..index]
+            .iter()
+            .any(|other| other.name == preset.name)
+        {
+            return Err(D::Error::custom(format!(
+                "duplicate trigger preset `{}`",
+                preset.name
+            )));
+        }
+    }
+    Ok(presets)
+}
+
+impl TriggerPresets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every preset shipped with the library
+    pub fn builtin() -> Self {
+        [
+            TriggerPreset::gun(),
+            TriggerPreset::automatic_gun(),
+            TriggerPreset::bow(),
+            TriggerPreset::accelerator(),
+            TriggerPreset::brake(),
+            TriggerPreset::brake_abs(),
+            TriggerPreset::horse(),
+        ]
+        .into_iter()
+        .fold(Self::new(), |presets, preset| presets.preset(preset))
+    }
+
+    pub fn preset(mut self, preset: TriggerPreset) -> Self {
+        self.insert(preset);
+        self
+    }
+
+    pub fn insert(&mut self, preset: TriggerPreset) {
+        match self
+            .presets
+            .iter_mut()
+            .find(|existing| existing.name == preset.name)
+        {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<TriggerPreset> {
+        let index = self.presets.iter().position(|preset| preset.name == name)?;
+        Some(self.presets.remove(index))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TriggerPreset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    /// Effect of the preset with the given name
+    pub fn effect(&self, name: &str) -> Option<TriggerEffect> {
+        self.get(name).map(|preset| preset.effect)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TriggerPreset> {
+        self.presets.iter()
+    }
+
+    /// Check every preset, returns the name of the first invalid one with its error. Presets loaded from a file
+    /// should be validated before use
+    pub fn validate(&self) -> Result<(), (String, TriggerEffectError)> {
+        for preset in &self.presets {
+            preset.validate().map_err(|e| (preset.name.clone(), e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_presets_are_valid() {
+        let presets = TriggerPresets::builtin();
+        assert_eq!(presets.iter().count(), 7);
+        assert_eq!(presets.validate(), Ok(()));
+    }
+
+    #[test]
+    fn insert_replaces_same_name() {
+        let mut presets = TriggerPresets::builtin();
+        presets.insert(TriggerPreset::new("gun", TriggerEffect::Off));
+        assert_eq!(presets.iter().count(), 7);
+        assert_eq!(presets.effect("gun"), Some(TriggerEffect::Off));
+        assert_eq!(
+            presets.remove("gun").map(|preset| preset.name),
+            Some("gun".to_owned())
+        );
+        assert_eq!(presets.get("gun"), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let presets = TriggerPresets::builtin();
+        let json = serde_json::to_string(&presets).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TriggerPresets>(&json).unwrap(),
+            presets
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn toml_round_trip() {
+        let presets = TriggerPresets::builtin();
+        let toml = toml::to_string(&presets).unwrap();
+        assert_eq!(toml::from_str::<TriggerPresets>(&toml).unwrap(), presets);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let json = r#"{"presets": [
+            {"name": "brake", "effect": {"Feedback": {"start": 3, "strength": 7}}},
+            {"name": "brake", "effect": "Off"}
+        ]}"#;
+        let error = serde_json::from_str::<TriggerPresets>(json).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("duplicate trigger preset `brake`"),
+            "{error}"
+        );
+
+        let toml = r#"
+            [[presets]]
+            name = "horse"
+            effect = "Off"
+
+            [[presets]]
+            name = "horse"
+            effect = "Off"
+        "#;
+        let error = toml::from_str::<TriggerPresets>(toml).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("duplicate trigger preset `horse`"),
+            "{error}"
+        );
+    }
+}
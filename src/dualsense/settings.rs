@@ -0,0 +1,296 @@
+use crate::{
+    properties::{
+        analog_pad::AnalogPad,
+        calibration::{DriftMonitor, StickCalibration},
+        curve::Curve,
+        dead_zone::DeadZone,
+        direction::{Direction, DirectionTracker},
+        dpad::DPad,
+        fusion::MahonyFilter,
+        gesture::GestureRecognizer,
+        gyro_aim::{FlickStick, GyroAim},
+        gyro_bias::{GyroBias, GyroBiasEstimator, GyroCalibrator},
+        motion::{ImuCalibration, MotionSample},
+        property::{ComboProperty, InputProperty},
+        report::{ReportStats, SEQUENCE_BYTE},
+        touch::{TouchEvent, TouchFrame, TouchTracker},
+        touch_gesture::{TouchGesture, TouchGestureRecognizer},
+        touch_zone::{TouchZones, ZoneButton},
+        trackpad::{MouseEvent, TrackpadMapper},
+        trigger::{Trigger, TriggerButton},
+    },
+    DualSense,
+};
+
+/// Configuration applied to the values decoded from the input report before they are handed to callbacks and
+/// combos
+#[derive(Default)]
+pub(crate) struct InputSettings {
+    pub(crate) left_calibration: StickCalibration,
+    pub(crate) right_calibration: StickCalibration,
+    pub(crate) left_drift: DriftMonitor,
+    pub(crate) right_drift: DriftMonitor,
+    pub(crate) left_dead_zone: DeadZone,
+    pub(crate) right_dead_zone: DeadZone,
+    pub(crate) left_curves: (Curve, Curve),
+    pub(crate) right_curves: (Curve, Curve),
+    pub(crate) l2_curve: Curve,
+    pub(crate) r2_curve: Curve,
+    pub(crate) left_direction: DirectionTracker,
+    pub(crate) right_direction: DirectionTracker,
+    pub(crate) l2_button: TriggerButton,
+    pub(crate) r2_button: TriggerButton,
+    pub(crate) imu_calibration: ImuCalibration,
+    pub(crate) orientation: MahonyFilter,
+    pub(crate) gyro_aim: GyroAim,
+    pub(crate) flick_stick: Option<FlickStick>,
+    pub(crate) last_motion_timestamp: Option<u32>,
+    pub(crate) gyro_bias: GyroBias,
+    pub(crate) gyro_estimator: Option<GyroBiasEstimator>,
+    pub(crate) gyro_calibrator: Option<GyroCalibrator>,
+    pub(crate) gestures: GestureRecognizer,
+    pub(crate) stats: ReportStats,
+    pub(crate) touch: TouchTracker,
+    pub(crate) touch_gestures: TouchGestureRecognizer,
+    pub(crate) trackpad: Option<TrackpadMapper>,
+    pub(crate) touch_zones: TouchZones,
+}
+
+/// Values computed from every report that are handed to their own callbacks rather than through `ComboProperty`
+pub(crate) struct ReportUpdate {
+    /// Camera movement for gyro aiming, in degrees
+    pub(crate) aim: (f32, f32),
+    pub(crate) touch_events: Vec<TouchEvent>,
+    pub(crate) touch_gestures: Vec<TouchGesture>,
+    pub(crate) mouse_events: Vec<MouseEvent>,
+}
+
+impl InputSettings {
+    /// Gyroscope bias in raw sensor units, subtracted from the values given to the raw gyroscope callbacks
+    pub(crate) fn raw_gyro_bias(&self) -> [i16; 3] {
+        let bias = [self.gyro_bias.x, self.gyro_bias.y, self.gyro_bias.z];
+        std::array::from_fn(|axis| self.imu_calibration.gyro[axis].raw_offset(bias[axis]))
+    }
+
+    /// Update the state that has to follow every report, whether or not a callback listens to it
+    pub(crate) fn update(&mut self, data: &[u8; 64]) -> ReportUpdate {
+        let motion = Self::decode_motion(data)
+            .with_calibration(self.imu_calibration)
+            .with_sequence(data[SEQUENCE_BYTE]);
+        self.stats.update(motion.sequence(), motion.timestamp());
+        if let Some(estimator) = self.gyro_estimator.as_mut() {
+            estimator.update(motion.gyro_uncorrected(), motion.accel());
+            self.gyro_bias = estimator.bias();
+        }
+        if let Some(calibrator) = self.gyro_calibrator.as_mut() {
+            calibrator.sample(motion.gyro_uncorrected());
+        }
+        let motion = motion.with_bias(self.gyro_bias);
+        self.orientation.update(&motion);
+
+        let dt = self
+            .last_motion_timestamp
+            .map(|last| motion.elapsed_seconds(last))
+            .unwrap_or_default();
+        self.last_motion_timestamp = Some(motion.timestamp());
+        self.gestures.update(motion.accel(), dt);
+
+        let ratchet_held = self
+            .gyro_aim
+            .ratchet_button()
+            .is_some_and(|button| self.decode(&button, data) == button);
+        let (mut x, y) =
+            self.gyro_aim
+                .update(motion.gyro(), self.orientation.up(), dt, ratchet_held);
+        if let Some(flick_stick) = self.flick_stick.as_mut() {
+            let pad = ComboProperty::RightPad(AnalogPad::default());
+            let pad = Self::decode_pad(pad, data)
+                .with_calibration(self.right_calibration)
+                .with_dead_zone(self.right_dead_zone);
+            x += flick_stick.update(&pad, dt);
+        }
+
+        let touch = ComboProperty::Touch(TouchFrame::default()).offset().bytes;
+        let frame = TouchFrame::new(&data[touch]);
+        let touch_events = self.touch.update(frame);
+        let touch_gestures = self.touch_gestures.update(frame, dt);
+        let mouse_events = match self.trackpad.as_mut() {
+            Some(trackpad) => trackpad.update(frame, Self::touchpad_clicked(data), dt),
+            None => Vec::new(),
+        };
+
+        ReportUpdate {
+            aim: (x, y),
+            touch_events,
+            touch_gestures,
+            mouse_events,
+        }
+    }
+
+    /// Decode a property from the report with the configuration applied. Properties derived from other inputs,
+    /// which need the configuration or state kept between reports, are only computed here
+    pub(crate) fn decode(&mut self, prop: &ComboProperty, data: &[u8; 64]) -> ComboProperty {
+        let prop = *prop;
+        match prop {
+            ComboProperty::LeftPadDirection(_) => {
+                let pad = self.left_pad(Self::decode_pad(prop, data));
+                ComboProperty::LeftPadDirection(Self::to_dpad(self.left_direction.update(&pad)))
+            }
+            ComboProperty::RightPadDirection(_) => {
+                let pad = self.right_pad(Self::decode_pad(prop, data));
+                ComboProperty::RightPadDirection(Self::to_dpad(self.right_direction.update(&pad)))
+            }
+            ComboProperty::LTPressed(_) => {
+                ComboProperty::LTPressed(self.l2_button.update(&Self::decode_trigger(prop, data)))
+            }
+            ComboProperty::RTPressed(_) => {
+                ComboProperty::RTPressed(self.r2_button.update(&Self::decode_trigger(prop, data)))
+            }
+            ComboProperty::Gesture(_) => ComboProperty::Gesture(self.gestures.current()),
+            ComboProperty::TouchZone(button) => {
+                let frame = TouchFrame::new(&data[prop.offset().bytes]);
+                let pressed =
+                    self.touch_zones
+                        .is_pressed(button.id, &frame, Self::touchpad_clicked(data));
+                ComboProperty::TouchZone(ZoneButton {
+                    id: button.id,
+                    pressed,
+                })
+            }
+            _ => self.apply(DualSense::extract_bytes_v2(&prop, data), data),
+        }
+    }
+
+    /// Apply the configuration to a property read from the report
+    fn apply(&mut self, prop: ComboProperty, data: &[u8]) -> ComboProperty {
+        match prop {
+            ComboProperty::LeftPad(pad) => {
+                let (x, y) = self.left_calibration.normalize(pad.raw().0, pad.raw().1);
+                self.left_drift.update(x, y);
+                ComboProperty::LeftPad(self.left_pad(pad))
+            }
+            ComboProperty::RightPad(pad) => {
+                let (x, y) = self.right_calibration.normalize(pad.raw().0, pad.raw().1);
+                self.right_drift.update(x, y);
+                ComboProperty::RightPad(self.right_pad(pad))
+            }
+            ComboProperty::LT(trigger) => ComboProperty::LT(trigger.with_curve(self.l2_curve)),
+            ComboProperty::RT(trigger) => ComboProperty::RT(trigger.with_curve(self.r2_curve)),
+            ComboProperty::Motion(sample) => ComboProperty::Motion(
+                sample
+                    .with_calibration(self.imu_calibration)
+                    .with_sequence(data[SEQUENCE_BYTE])
+                    .with_bias(self.gyro_bias),
+            ),
+            _ => prop,
+        }
+    }
+
+    fn left_pad(&self, pad: AnalogPad) -> AnalogPad {
+        pad.with_calibration(self.left_calibration)
+            .with_dead_zone(self.left_dead_zone)
+            .with_curves(self.left_curves.0, self.left_curves.1)
+    }
+
+    fn right_pad(&self, pad: AnalogPad) -> AnalogPad {
+        pad.with_calibration(self.right_calibration)
+            .with_dead_zone(self.right_dead_zone)
+            .with_curves(self.right_curves.0, self.right_curves.1)
+    }
+
+    fn decode_pad(prop: ComboProperty, data: &[u8]) -> AnalogPad {
+        let byte = prop.offset().bytes.start;
+        AnalogPad::new(data[byte], data[byte + 1])
+    }
+
+    fn touchpad_clicked(data: &[u8]) -> bool {
+        let data: &[u8; 64] = data.try_into().unwrap();
+        DualSense::extract_bits(&InputProperty::TouchPad.offset(), data) == 1
+    }
+
+    fn decode_motion(data: &[u8]) -> MotionSample {
+        let bytes = ComboProperty::Motion(MotionSample::default())
+            .offset()
+            .bytes;
+        MotionSample::new(&data[bytes])
+    }
+
+    fn decode_trigger(prop: ComboProperty, data: &[u8]) -> Trigger {
+        Trigger::new(data[prop.offset().bytes.start])
+    }
+
+    fn to_dpad(direction: Option<Direction>) -> DPad {
+        direction
+            .map(|direction| direction.to_dpad())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::{gesture::Gesture, trigger::TriggerThreshold};
+
+    #[test]
+    fn raw_gyro_bias() {
+        let mut settings = InputSettings::default();
+        assert_eq!(settings.raw_gyro_bias(), [0, 0, 0]);
+        // nominal sensitivity of 2000 degrees/second for 32767 units
+        settings.gyro_bias = GyroBias {
+            x: 1.0,
+            y: -0.5,
+            z: 2000.0,
+        };
+        assert_eq!(settings.raw_gyro_bias(), [16, -8, i16::MAX]);
+    }
+
+    #[test]
+    fn trigger_buttons_keep_their_state_between_reports() {
+        let mut settings = InputSettings {
+            r2_button: TriggerButton::new(TriggerThreshold::new(0.8, 0.2)),
+            ..Default::default()
+        };
+        let mut pressed = |l2: u8, r2: u8| {
+            let mut data = [0; 64];
+            data[5] = l2;
+            data[6] = r2;
+            let l2 = settings.decode(&ComboProperty::LTPressed(false), &data);
+            let r2 = settings.decode(&ComboProperty::RTPressed(false), &data);
+            (l2.to_bool(), r2.to_bool())
+        };
+        assert_eq!(pressed(0, 0), (false, false));
+        assert_eq!(pressed(140, 140), (true, false));
+        assert_eq!(pressed(110, 220), (true, true));
+        assert_eq!(pressed(90, 60), (false, true));
+        assert_eq!(pressed(0, 40), (false, false));
+    }
+
+    #[test]
+    fn derived_properties() {
+        let mut settings = InputSettings {
+            touch_zones: TouchZones::halves(),
+            ..Default::default()
+        };
+        let mut data = [0; 64];
+        // left stick pushed up, right stick resting
+        data[1..5].copy_from_slice(&[128, 0, 128, 128]);
+        // a finger at x 1500 on the touchpad, the second one lifted
+        data[33..41].copy_from_slice(&[0x01, 0xDC, 0x05, 0x20, 0x80, 0, 0, 0]);
+        let decode = |settings: &mut InputSettings, prop| settings.decode(&prop, &data);
+        assert_eq!(
+            decode(&mut settings, ComboProperty::LeftPadDirection(DPad::None)),
+            ComboProperty::LeftPadDirection(DPad::Up)
+        );
+        assert_eq!(
+            decode(&mut settings, ComboProperty::RightPadDirection(DPad::Up)),
+            ComboProperty::RightPadDirection(DPad::None)
+        );
+        let zone = |id, pressed| ComboProperty::TouchZone(ZoneButton { id, pressed });
+        assert_eq!(decode(&mut settings, zone(0, false)), zone(0, false));
+        assert_eq!(decode(&mut settings, zone(1, false)), zone(1, true));
+        assert_eq!(
+            decode(&mut settings, ComboProperty::Gesture(Gesture::Tap)),
+            ComboProperty::Gesture(Gesture::None)
+        );
+    }
+}
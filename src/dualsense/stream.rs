@@ -1,31 +1,66 @@
 use hidapi::{HidApi, HidDevice};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashMap,
     ffi::CString,
     sync::{Arc, Mutex},
     thread::{self, sleep, JoinHandle},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use crate::{
     combo::{Combo, ComboId},
     properties::{
         analog_pad::AnalogPad,
+        animation::{AnimationHandle, LightbarAnimation},
+        calibration::{DriftMonitor, StickCalibration, StickCalibrator},
+        curve::Curve,
+        dead_zone::DeadZone,
+        direction::DirectionTracker,
         dpad::DPad,
+        fusion::{MahonyFilter, Quaternion},
+        gesture::{Gesture, GestureRecognizer},
+        gyro_aim::{FlickStick, GyroAim},
+        gyro_bias::{GyroBias, GyroBiasEstimator, GyroCalibrator},
+        haptics::{HapticEffect, HapticHandle, Haptics},
+        lightbar::{Color, Lightbar},
+        motion::{
+            ImuCalibration, MotionSample, IMU_CALIBRATION_REPORT_ID, IMU_CALIBRATION_REPORT_SIZE,
+        },
+        mute::{MuteLed, PowerSave},
+        offset::Offset,
+        output_batch::{self, OutputBatch, OutputChange},
+        player_leds::{LedBrightness, PlayerLeds, PlayerLedsError},
         property::{ComboProperty, InputProperty, OutputProperty},
+        report::{CurrentReport, ReportStamp, ReportStats},
+        rumble::{Rumble, RumbleHandle, RumblePattern, RumblePlayer},
         symbols::Symbols,
-        trigger::Trigger,
-        trigger_effect::TriggerEffect,
+        touch::{TouchEvent, TouchFrame},
+        touch_gesture::{TouchGesture, TouchGestureRecognizer},
+        touch_zone::{TouchZones, ZoneButton},
+        trackpad::{MouseEvent, TrackpadMapper},
+        trigger::{Trigger, TriggerButton, TriggerThreshold},
+        trigger_effect::{TriggerEffect, TriggerEffectError},
+        trigger_feedback::TriggerFeedbackStatus,
         valuetype::ValueType,
     },
+    settings::InputSettings,
 };
 
 const VENDOR_ID: u16 = 1356;
 const PRODUCT_ID: u16 = 3302;
 const PACKET_SIZE: usize = 64;
+/// Time between two updates of the output values
+const OUTPUT_INTERVAL: Duration = Duration::from_millis(10);
+/// Longest time a read holds the device before letting the output thread write, in milliseconds
+const READ_TIMEOUT_MS: i32 = 10;
 
 type CBFunction = Box<dyn FnMut(ValueType) + Send>;
 type CBFunction2 = Box<dyn FnMut(ComboProperty) + Send>;
+type AimFunction = Box<dyn FnMut(f32, f32) + Send>;
+type TouchEventFunction = Box<dyn FnMut(TouchEvent) + Send>;
+type TouchGestureFunction = Box<dyn FnMut(TouchGesture) + Send>;
+type MouseFunction = Box<dyn FnMut(MouseEvent) + Send>;
+type TriggerFeedbackFunction = Box<dyn FnMut(TriggerFeedbackStatus, TriggerFeedbackStatus) + Send>;
 type Artex<T> = Arc<Mutex<T>>;
 
 /// Main struct used for interacting with the controller. Everything is thread safe to allow reading, writing,
@@ -33,12 +68,25 @@ type Artex<T> = Arc<Mutex<T>>;
 pub struct DualSense {
     device: Artex<HidDevice>,
     callbacks: Artex<HashMap<InputProperty, Vec<CBFunction>>>,
+    callback_cache: Artex<HashMap<InputProperty, ValueType>>,
     callbacks_v2: Artex<HashMap<ComboProperty, Vec<CBFunction2>>>,
     // TODO: provide better ergonomics
     callback_cache_v2: Artex<HashMap<ComboProperty, ComboProperty>>,
     output_cache: Artex<HashMap<OutputProperty, u8>>,
     output_cache_changed: Artex<bool>,
+    lightbar: Artex<Lightbar>,
+    rumble: Artex<RumblePlayer>,
+    haptics: Artex<Haptics>,
+    /// Start of the timeline the haptic effects are played on
+    haptics_origin: Instant,
     combos: Artex<Vec<Combo>>,
+    settings: Artex<InputSettings>,
+    gyro_aim_callbacks: Artex<Vec<AimFunction>>,
+    touch_event_callbacks: Artex<Vec<TouchEventFunction>>,
+    touch_gesture_callbacks: Artex<Vec<TouchGestureFunction>>,
+    mouse_callbacks: Artex<Vec<MouseFunction>>,
+    trigger_feedback_callbacks: Artex<Vec<TriggerFeedbackFunction>>,
+    current_report: CurrentReport,
 }
 
 impl DualSense {
@@ -64,33 +112,78 @@ impl DualSense {
     }
 
     fn new_with_device(device: HidDevice) -> Self {
+        let settings = InputSettings {
+            imu_calibration: Self::read_imu_calibration(&device),
+            gyro_estimator: Some(GyroBiasEstimator::default()),
+            ..Default::default()
+        };
         let mut dualsense = Self {
             device: Arc::new(Mutex::new(device)),
             callbacks: Arc::new(Mutex::new(HashMap::new())),
+            callback_cache: Arc::new(Mutex::new(HashMap::new())),
             callback_cache_v2: Arc::new(Mutex::new(HashMap::new())),
             output_cache: Arc::new(Mutex::new(HashMap::new())),
             output_cache_changed: Arc::new(Mutex::new(false)),
+            lightbar: Arc::new(Mutex::new(Lightbar::default())),
+            rumble: Arc::new(Mutex::new(RumblePlayer::default())),
+            haptics: Arc::new(Mutex::new(Haptics::default())),
+            haptics_origin: Instant::now(),
             combos: Arc::new(Mutex::new(Vec::new())),
             callbacks_v2: Arc::new(Mutex::new(HashMap::new())),
+            settings: Arc::new(Mutex::new(settings)),
+            gyro_aim_callbacks: Arc::new(Mutex::new(Vec::new())),
+            touch_event_callbacks: Arc::new(Mutex::new(Vec::new())),
+            touch_gesture_callbacks: Arc::new(Mutex::new(Vec::new())),
+            mouse_callbacks: Arc::new(Mutex::new(Vec::new())),
+            trigger_feedback_callbacks: Arc::new(Mutex::new(Vec::new())),
+            current_report: CurrentReport::default(),
         };
         dualsense.prepopulate_combos_callbacks();
         dualsense
     }
 
-    /// Start listening to HID packets from the controller
+    /// Read the gyroscope and accelerometer calibration stored on the controller, falls back to the nominal
+    /// sensitivity if it can't be read
+    fn read_imu_calibration(device: &HidDevice) -> ImuCalibration {
+        let mut buf = [0u8; IMU_CALIBRATION_REPORT_SIZE];
+        buf[0] = IMU_CALIBRATION_REPORT_ID;
+        match device.get_feature_report(&mut buf) {
+            Ok(_) => ImuCalibration::from_feature_report(&buf).unwrap_or_default(),
+            Err(e) => {
+                eprintln!("Error on reading the IMU calibration, using defaults {e}");
+                ImuCalibration::default()
+            }
+        }
+    }
+
+    /// Start listening to HID packets from the controller and sending the output values
     pub fn run(&mut self) -> JoinHandle<()> {
+        self.run_output();
+
         let device = Arc::clone(&self.device);
+        let callbacks = Arc::clone(&self.callbacks);
+        let cache = Arc::clone(&self.callback_cache);
         let callbacks_v2 = Arc::clone(&self.callbacks_v2);
         let cache_v2 = Arc::clone(&self.callback_cache_v2);
-        let output_cache = Arc::clone(&self.output_cache);
-        let output_cache_changed = Arc::clone(&self.output_cache_changed);
         let combos = Arc::clone(&self.combos);
+        let settings = Arc::clone(&self.settings);
+        let gyro_aim_callbacks = Arc::clone(&self.gyro_aim_callbacks);
+        let touch_event_callbacks = Arc::clone(&self.touch_event_callbacks);
+        let touch_gesture_callbacks = Arc::clone(&self.touch_gesture_callbacks);
+        let mouse_callbacks = Arc::clone(&self.mouse_callbacks);
+        let trigger_feedback_callbacks = Arc::clone(&self.trigger_feedback_callbacks);
+        let current_report = self.current_report.clone();
 
         thread::spawn(move || loop {
             let mut buf = [0u8; PACKET_SIZE];
-            let bytes_read = device.lock().unwrap().read(&mut buf);
+            let bytes_read = device
+                .lock()
+                .unwrap()
+                .read_timeout(&mut buf, READ_TIMEOUT_MS);
             match bytes_read {
                 Ok(PACKET_SIZE) => {}
+                // no report within the timeout
+                Ok(0) => continue,
                 Ok(actual_size) => {
                     eprintln!("Packet size mismatch, ignoring values ({actual_size})");
                     continue;
@@ -101,39 +194,321 @@ impl DualSense {
                 }
             }
 
+            current_report.set(ReportStamp::new(&buf));
+            let raw_gyro_bias = settings.lock().unwrap().raw_gyro_bias();
+            Self::packet_received(
+                &mut callbacks.lock().unwrap(),
+                &mut cache.lock().unwrap(),
+                raw_gyro_bias,
+                &buf,
+            );
+            let update = settings.lock().unwrap().update(&buf);
+            for cb in gyro_aim_callbacks.lock().unwrap().iter_mut() {
+                cb(update.aim.0, update.aim.1);
+            }
+            for event in update.touch_events {
+                for cb in touch_event_callbacks.lock().unwrap().iter_mut() {
+                    cb(event);
+                }
+            }
+            for gesture in update.touch_gestures {
+                for cb in touch_gesture_callbacks.lock().unwrap().iter_mut() {
+                    cb(gesture);
+                }
+            }
+            for event in update.mouse_events {
+                for cb in mouse_callbacks.lock().unwrap().iter_mut() {
+                    cb(event);
+                }
+            }
+            let left_feedback = TriggerFeedbackStatus::left(&buf);
+            let right_feedback = TriggerFeedbackStatus::right(&buf);
+            for cb in trigger_feedback_callbacks.lock().unwrap().iter_mut() {
+                cb(left_feedback, right_feedback);
+            }
             Self::packet_received_v2(
                 &mut callbacks_v2.lock().unwrap(),
                 &mut cache_v2.lock().unwrap(),
                 &mut combos.lock().unwrap(),
+                &mut settings.lock().unwrap(),
                 &buf,
             );
-            if *output_cache_changed.lock().unwrap() {
-                Self::write(&device.lock().unwrap(), &output_cache.lock().unwrap());
-                *output_cache_changed.lock().unwrap() = false;
-            }
-            sleep(Duration::from_millis(50));
         })
     }
 
+    /// Advance the lightbar animations, rumble patterns and haptic effects and send the output report when
+    /// something changed, every `OUTPUT_INTERVAL`
+    fn run_output(&mut self) {
+        let device = Arc::clone(&self.device);
+        let output_cache = Arc::clone(&self.output_cache);
+        let output_cache_changed = Arc::clone(&self.output_cache_changed);
+        let lightbar = Arc::clone(&self.lightbar);
+        let rumble = Arc::clone(&self.rumble);
+        let haptics = Arc::clone(&self.haptics);
+        let haptics_origin = self.haptics_origin;
+
+        thread::spawn(move || loop {
+            let now = Instant::now();
+            if let Some(color) = lightbar.lock().unwrap().tick(now) {
+                Self::write_lightbar(&output_cache, &output_cache_changed, color);
+            }
+            let base = rumble.lock().unwrap().tick(now);
+            let seconds = now.duration_since(haptics_origin).as_secs_f32();
+            let motors = {
+                let mut haptics = haptics.lock().unwrap();
+                haptics.update(seconds);
+                haptics.output(seconds, base)
+            };
+            Self::write_rumble(&output_cache, &output_cache_changed, motors);
+
+            // the flag is cleared with the cache locked, so a change made after the copy is sent next time
+            let report = {
+                let output_cache = output_cache.lock().unwrap();
+                let mut changed = output_cache_changed.lock().unwrap();
+                std::mem::take(&mut *changed).then(|| output_cache.clone())
+            };
+            if let Some(report) = report {
+                Self::write(&device.lock().unwrap(), &report);
+            }
+            sleep(OUTPUT_INTERVAL);
+        });
+    }
+
     pub fn set_light_red(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::Red, value);
+        let color = self.lightbar_color();
+        self.set_lightbar(Color {
+            red: value,
+            ..color
+        });
     }
 
     pub fn set_light_green(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::Green, value);
+        let color = self.lightbar_color();
+        self.set_lightbar(Color {
+            green: value,
+            ..color
+        });
     }
 
     pub fn set_light_blue(&mut self, value: u8) {
-        self.output_cache
+        let color = self.lightbar_color();
+        self.set_lightbar(Color {
+            blue: value,
+            ..color
+        });
+    }
+
+    /// Set the lightbar color, all channels are sent in the same report. Stops the playing animation
+    pub fn set_lightbar(&mut self, color: Color) {
+        self.apply_batch(OutputBatch::default().set_lightbar(color));
+    }
+
+    /// Color last written to the output report, with the brightness and the playing animation applied
+    pub fn lightbar(&self) -> Color {
+        Color::new(
+            self.output_value(OutputProperty::Red),
+            self.output_value(OutputProperty::Green),
+            self.output_value(OutputProperty::Blue),
+        )
+    }
+
+    /// Color set with `Self::set_lightbar`, shown while no animation plays, before the brightness is applied
+    pub fn lightbar_color(&self) -> Color {
+        self.lightbar.lock().unwrap().color
+    }
+
+    /// Scale the lightbar color by `brightness`, in the [0, 1] interval. 1 by default
+    pub fn set_lightbar_brightness(&mut self, brightness: f32) {
+        self.apply_batch(OutputBatch::default().set_lightbar_brightness(brightness));
+    }
+
+    pub fn lightbar_brightness(&self) -> f32 {
+        self.lightbar.lock().unwrap().brightness
+    }
+
+    /// Apply several output changes at once, they are all sent in the same report. Nothing is changed if a trigger
+    /// effect is invalid
+    ///
+    /// ```rust,no_run
+    /// use dualsense_rs::{
+    ///     properties::{lightbar::Color, trigger_effect::TriggerEffect},
+    ///     DualSense,
+    /// };
+    ///
+    /// let mut controller = DualSense::default();
+    /// controller
+    ///     .update(|out| {
+    ///         out.set_lightbar(Color::RED)
+    ///             .set_rumble(0, 200)
+    ///             .set_right_trigger_effect(TriggerEffect::Weapon {
+    ///                 start: 2,
+    ///                 end: 5,
+    ///                 strength: 8,
+    ///             });
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn update<F>(&mut self, f: F) -> Result<(), TriggerEffectError>
+    where
+        F: FnOnce(&mut OutputBatch),
+    {
+        let mut batch = OutputBatch::default();
+        f(&mut batch);
+        if let Some(e) = batch.error {
+            return Err(e);
+        }
+        self.apply_batch(&mut batch);
+        Ok(())
+    }
+
+    fn apply_batch(&mut self, batch: &mut OutputBatch) {
+        let now = Instant::now();
+        if batch.lightbar.is_some() || batch.lightbar_brightness.is_some() {
+            let mut lightbar = self.lightbar.lock().unwrap();
+            if let Some(color) = batch.lightbar {
+                lightbar.color = color;
+                lightbar.stop(None);
+            }
+            if let Some(brightness) = batch.lightbar_brightness {
+                lightbar.brightness = brightness;
+            }
+            let color = lightbar.output(now);
+            batch.changes.extend([
+                OutputChange::Set(OutputProperty::Red, color.red),
+                OutputChange::Set(OutputProperty::Green, color.green),
+                OutputChange::Set(OutputProperty::Blue, color.blue),
+            ]);
+        }
+        if let Some(value) = batch.rumble {
+            let base = {
+                let mut rumble = self.rumble.lock().unwrap();
+                rumble.rumble = value;
+                rumble.stop(None);
+                rumble.output(now)
+            };
+            let seconds = now.duration_since(self.haptics_origin).as_secs_f32();
+            let motors = self.haptics.lock().unwrap().output(seconds, base);
+            batch.changes.extend([
+                OutputChange::Set(OutputProperty::RumbleLow, motors.low),
+                OutputChange::Set(OutputProperty::RumbleHigh, motors.high),
+            ]);
+        }
+
+        let mut output_cache = self.output_cache.lock().unwrap();
+        if batch.write_to(&mut output_cache) {
+            *self.output_cache_changed.lock().unwrap() = true;
+        }
+    }
+
+    /// Play an animation over the lightbar color, replacing the playing one. Frames are computed on the thread
+    /// started by `Self::run`
+    pub fn play_lightbar_animation(&mut self, animation: LightbarAnimation) -> AnimationHandle {
+        let id = self
+            .lightbar
             .lock()
             .unwrap()
-            .insert(OutputProperty::Blue, value);
+            .play(animation, Instant::now());
+        AnimationHandle::new(id, Arc::clone(&self.lightbar))
+    }
+
+    /// Stop the playing animation and go back to the lightbar color
+    pub fn stop_lightbar_animation(&mut self) {
+        self.lightbar.lock().unwrap().stop(None);
+    }
+
+    fn write_lightbar(
+        output_cache: &Mutex<HashMap<OutputProperty, u8>>,
+        output_cache_changed: &Mutex<bool>,
+        color: Color,
+    ) {
+        Self::write_outputs(
+            output_cache,
+            output_cache_changed,
+            &[
+                (OutputProperty::Red, color.red),
+                (OutputProperty::Green, color.green),
+                (OutputProperty::Blue, color.blue),
+            ],
+        );
+    }
+
+    /// Set both motor intensities, they are sent in the same report. Stops the playing pattern
+    pub fn set_rumble(&mut self, low_freq: u8, high_freq: u8) {
+        self.apply_batch(OutputBatch::default().set_rumble(low_freq, high_freq));
+    }
+
+    /// Intensities the motors have while no pattern plays
+    pub fn rumble(&self) -> Rumble {
+        self.rumble.lock().unwrap().rumble
+    }
+
+    /// Rumble for `duration`, then go back to the intensities given to `Self::set_rumble`
+    pub fn rumble_for(&mut self, low_freq: u8, high_freq: u8, duration: Duration) -> RumbleHandle {
+        self.play_rumble_pattern(
+            RumblePattern::new().step(Rumble::new(low_freq, high_freq), duration.as_secs_f32()),
+        )
+    }
+
+    /// Play a pattern over the intensities given to `Self::set_rumble`, replacing the playing one. Steps are
+    /// timed on the thread started by `Self::run`
+    pub fn play_rumble_pattern(&mut self, pattern: RumblePattern) -> RumbleHandle {
+        let id = self.rumble.lock().unwrap().play(pattern, Instant::now());
+        RumbleHandle::new(id, Arc::clone(&self.rumble))
+    }
+
+    /// Stop the playing pattern and go back to the intensities given to `Self::set_rumble`
+    pub fn stop_rumble_pattern(&mut self) {
+        self.rumble.lock().unwrap().stop(None);
+    }
+
+    /// Play a haptic effect mixed with the other playing effects, over the intensities given to `Self::set_rumble`
+    /// and the playing pattern
+    pub fn play_haptic(&mut self, effect: HapticEffect) -> HapticHandle {
+        let seconds = self.haptics_origin.elapsed().as_secs_f32();
+        let id = self.haptics.lock().unwrap().play(effect, seconds);
+        HapticHandle::new(id, Arc::clone(&self.haptics))
+    }
+
+    /// Stop every playing haptic effect
+    pub fn stop_haptics(&mut self) {
+        self.haptics.lock().unwrap().stop_all();
+    }
+
+    /// Let newer firmware emulate the classic rumble motors with the haptic actuators, which feels closer to
+    /// older controllers. Disabled by default
+    pub fn set_improved_rumble(&mut self, enabled: bool) {
+        self.apply_batch(OutputBatch::default().set_improved_rumble(enabled));
+    }
+
+    fn write_rumble(
+        output_cache: &Mutex<HashMap<OutputProperty, u8>>,
+        output_cache_changed: &Mutex<bool>,
+        rumble: Rumble,
+    ) {
+        Self::write_outputs(
+            output_cache,
+            output_cache_changed,
+            &[
+                (OutputProperty::RumbleLow, rumble.low),
+                (OutputProperty::RumbleHigh, rumble.high),
+            ],
+        );
+    }
+
+    /// Update several output bytes under the same lock, so they are sent in the same report. Nothing is sent if
+    /// the values did not change
+    fn write_outputs(
+        output_cache: &Mutex<HashMap<OutputProperty, u8>>,
+        output_cache_changed: &Mutex<bool>,
+        values: &[(OutputProperty, u8)],
+    ) {
+        let mut output_cache = output_cache.lock().unwrap();
+        let changed = values.iter().fold(false, |changed, (property, value)| {
+            output_cache.insert(*property, *value) != Some(*value) || changed
+        });
+        if changed {
+            *output_cache_changed.lock().unwrap() = true;
+        }
     }
 
     /// Provide a callback to be called when the left stick's coordinates change
@@ -146,6 +521,140 @@ impl DualSense {
         self.register_analog(ComboProperty::RightPad(AnalogPad::default()), cb);
     }
 
+    /// Sample the left stick to find its resting position and range of motion, the stick must be left alone for
+    /// the `rest` duration and then rotated along its edge for the `rotation` duration. The result is applied
+    /// right away and returned so it can be stored. Blocks the current thread, `Self::run` must be called first
+    pub fn calibrate_left_pad(&mut self, rest: Duration, rotation: Duration) -> StickCalibration {
+        let calibration =
+            self.calibrate_pad(ComboProperty::LeftPad(AnalogPad::default()), rest, rotation);
+        self.set_left_pad_calibration(calibration);
+        calibration
+    }
+
+    /// Sample the right stick to find its resting position and range of motion, the stick must be left alone
+    /// for the `rest` duration and then rotated along its edge for the `rotation` duration. The result is
+    /// applied right away and returned so it can be stored. Blocks the current thread, `Self::run` must be
+    /// called first
+    pub fn calibrate_right_pad(&mut self, rest: Duration, rotation: Duration) -> StickCalibration {
+        let calibration = self.calibrate_pad(
+            ComboProperty::RightPad(AnalogPad::default()),
+            rest,
+            rotation,
+        );
+        self.set_right_pad_calibration(calibration);
+        calibration
+    }
+
+    /// Set the calibration applied to the values given to `Self::on_left_pad_changed` callbacks and combos, none by default
+    pub fn set_left_pad_calibration(&mut self, calibration: StickCalibration) {
+        let mut settings = self.settings.lock().unwrap();
+        settings.left_calibration = calibration;
+        settings.left_drift.reset();
+    }
+
+    /// Set the calibration applied to the values given to `Self::on_right_pad_changed` callbacks and combos, none by default
+    pub fn set_right_pad_calibration(&mut self, calibration: StickCalibration) {
+        let mut settings = self.settings.lock().unwrap();
+        settings.right_calibration = calibration;
+        settings.right_drift.reset();
+    }
+
+    pub fn left_pad_calibration(&self) -> StickCalibration {
+        self.settings.lock().unwrap().left_calibration
+    }
+
+    pub fn right_pad_calibration(&self) -> StickCalibration {
+        self.settings.lock().unwrap().right_calibration
+    }
+
+    /// Set how the resting position of the left stick is tracked
+    pub fn set_left_pad_drift_monitor(&mut self, monitor: DriftMonitor) {
+        self.settings.lock().unwrap().left_drift = monitor;
+    }
+
+    /// Set how the resting position of the right stick is tracked
+    pub fn set_right_pad_drift_monitor(&mut self, monitor: DriftMonitor) {
+        self.settings.lock().unwrap().right_drift = monitor;
+    }
+
+    /// Where the left stick has been resting lately, check `DriftMonitor::is_drifting`
+    pub fn left_pad_drift(&self) -> DriftMonitor {
+        self.settings.lock().unwrap().left_drift
+    }
+
+    /// Where the right stick has been resting lately, check `DriftMonitor::is_drifting`
+    pub fn right_pad_drift(&self) -> DriftMonitor {
+        self.settings.lock().unwrap().right_drift
+    }
+
+    /// Move the center of the left stick's calibration to where the stick has been resting lately
+    pub fn compensate_left_pad_drift(&mut self) {
+        let mut settings = self.settings.lock().unwrap();
+        let offset = settings.left_drift.offset();
+        settings.left_calibration.recenter(offset);
+        settings.left_drift.reset();
+    }
+
+    /// Move the center of the right stick's calibration to where the stick has been resting lately
+    pub fn compensate_right_pad_drift(&mut self) {
+        let mut settings = self.settings.lock().unwrap();
+        let offset = settings.right_drift.offset();
+        settings.right_calibration.recenter(offset);
+        settings.right_drift.reset();
+    }
+
+    /// Set the dead zone applied to the values given to `Self::on_left_pad_changed` callbacks and combos
+    pub fn set_left_pad_dead_zone(&mut self, dead_zone: DeadZone) {
+        self.settings.lock().unwrap().left_dead_zone = dead_zone;
+    }
+
+    /// Set the dead zone applied to the values given to `Self::on_right_pad_changed` callbacks and combos
+    pub fn set_right_pad_dead_zone(&mut self, dead_zone: DeadZone) {
+        self.settings.lock().unwrap().right_dead_zone = dead_zone;
+    }
+
+    /// Set the response curves applied to the X and Y axes of the left stick, after the dead zone
+    pub fn set_left_pad_curves(&mut self, x: Curve, y: Curve) {
+        self.settings.lock().unwrap().left_curves = (x, y);
+    }
+
+    /// Set the response curves applied to the X and Y axes of the right stick, after the dead zone
+    pub fn set_right_pad_curves(&mut self, x: Curve, y: Curve) {
+        self.settings.lock().unwrap().right_curves = (x, y);
+    }
+
+    /// Set how the left stick is split into directions for `Self::on_left_pad_direction_changed` callbacks and
+    /// combos
+    pub fn set_left_pad_direction_tracker(&mut self, tracker: DirectionTracker) {
+        self.settings.lock().unwrap().left_direction = tracker;
+    }
+
+    /// Set how the right stick is split into directions for `Self::on_right_pad_direction_changed` callbacks and
+    /// combos
+    pub fn set_right_pad_direction_tracker(&mut self, tracker: DirectionTracker) {
+        self.settings.lock().unwrap().right_direction = tracker;
+    }
+
+    /// Set the response curve applied to the values given to `Self::on_l2_changed` callbacks and combos
+    pub fn set_l2_curve(&mut self, curve: Curve) {
+        self.settings.lock().unwrap().l2_curve = curve;
+    }
+
+    /// Set the response curve applied to the values given to `Self::on_r2_changed` callbacks and combos
+    pub fn set_r2_curve(&mut self, curve: Curve) {
+        self.settings.lock().unwrap().r2_curve = curve;
+    }
+
+    /// Provide a callback to be called when the left stick, used as a dpad, changes direction
+    pub fn on_left_pad_direction_changed(&mut self, cb: Box<dyn FnMut(DPad) + Send>) {
+        self.register_dpad(ComboProperty::LeftPadDirection(DPad::None), cb);
+    }
+
+    /// Provide a callback to be called when the right stick, used as a dpad, changes direction
+    pub fn on_right_pad_direction_changed(&mut self, cb: Box<dyn FnMut(DPad) + Send>) {
+        self.register_dpad(ComboProperty::RightPadDirection(DPad::None), cb);
+    }
+
     /// Provide a callback to be called when the L1 button is pressed
     pub fn on_l1_changed<F>(&mut self, cb: &'static F)
     where
@@ -172,6 +681,26 @@ impl DualSense {
         self.register_trigger(ComboProperty::RT(Trigger::default()), cb);
     }
 
+    /// Provide a callback to be called when the L2 trigger, used as a digital button, is pressed or released
+    pub fn on_l2_pressed(&mut self, cb: Box<dyn FnMut(bool) + Send>) {
+        self.register_button(ComboProperty::LTPressed(false), cb);
+    }
+
+    /// Provide a callback to be called when the R2 trigger, used as a digital button, is pressed or released
+    pub fn on_r2_pressed(&mut self, cb: Box<dyn FnMut(bool) + Send>) {
+        self.register_button(ComboProperty::RTPressed(false), cb);
+    }
+
+    /// Set the actuation points of the L2 trigger for `Self::on_l2_pressed` callbacks and combos
+    pub fn set_l2_threshold(&mut self, threshold: TriggerThreshold) {
+        self.settings.lock().unwrap().l2_button = TriggerButton::new(threshold);
+    }
+
+    /// Set the actuation points of the R2 trigger for `Self::on_r2_pressed` callbacks and combos
+    pub fn set_r2_threshold(&mut self, threshold: TriggerThreshold) {
+        self.settings.lock().unwrap().r2_button = TriggerButton::new(threshold);
+    }
+
     /// Provide a callback to be called when the L3 button is pressed
     pub fn on_l3_changed<F>(&mut self, cb: &'static F)
     where
@@ -238,7 +767,150 @@ impl DualSense {
         self.register_bool(InputProperty::PlayStation, cb);
     }
 
-    /// Provide a callback to be called when the gyroscope X axis is changed
+    /// Provide a callback to be called with every gyroscope and accelerometer reading, in physical units
+    pub fn on_motion(&mut self, mut cb: Box<dyn FnMut(MotionSample) + Send>) {
+        self.callbacks_v2
+            .lock()
+            .unwrap()
+            .entry(ComboProperty::Motion(MotionSample::default()))
+            .or_default()
+            .push(Box::new(move |x| cb(x.into())));
+    }
+
+    /// Number of reports received and dropped, and the rate the controller sends them at
+    pub fn stats(&self) -> ReportStats {
+        self.settings.lock().unwrap().stats
+    }
+
+    /// Sequence counter and sensor timestamp of the report whose values are being handed to callbacks. Callbacks
+    /// can keep the handle to know which report their value comes from
+    ///
+    /// ```rust,no_run
+    /// use dualsense_rs::DualSense;
+    ///
+    /// let mut controller = DualSense::default();
+    /// let report = controller.current_report();
+    /// controller.on_left_pad_changed(Box::new(move |lp| {
+    ///     let stamp = report.stamp();
+    ///     println!("{} at {}: {:?}", stamp.sequence, stamp.timestamp, lp.normalize());
+    /// }));
+    /// ```
+    pub fn current_report(&self) -> CurrentReport {
+        self.current_report.clone()
+    }
+
+    /// Provide a callback to be called when the controller is shaken, tilted, turned upside down or tapped, and
+    /// with `Gesture::None` once the gesture ends
+    pub fn on_gesture(&mut self, mut cb: Box<dyn FnMut(Gesture) + Send>) {
+        self.callbacks_v2
+            .lock()
+            .unwrap()
+            .entry(ComboProperty::Gesture(Gesture::None))
+            .or_default()
+            .push(Box::new(move |x| cb(x.into())));
+    }
+
+    /// Configure the thresholds used to recognize the gestures given to `Self::on_gesture` callbacks and combos
+    pub fn set_gesture_recognizer(&mut self, recognizer: GestureRecognizer) {
+        self.settings.lock().unwrap().gestures = recognizer;
+    }
+
+    /// Calibration used to convert the gyroscope and accelerometer values given to `Self::on_motion` callbacks
+    pub fn imu_calibration(&self) -> ImuCalibration {
+        self.settings.lock().unwrap().imu_calibration
+    }
+
+    /// Average the gyroscope for the given duration to find its bias, the controller must be left still. The
+    /// result is applied right away and returned so it can be stored. Blocks the current thread, `Self::run` must
+    /// be called first
+    pub fn calibrate_gyro(&mut self, duration: Duration) -> GyroBias {
+        self.settings.lock().unwrap().gyro_calibrator = Some(GyroCalibrator::default());
+        sleep(duration);
+        let calibrator = self.settings.lock().unwrap().gyro_calibrator.take();
+        let bias = calibrator.unwrap_or_default().finish();
+        self.set_gyro_bias(bias);
+        bias
+    }
+
+    /// Set the bias removed from the gyroscope readings given to `Self::on_motion` callbacks and used for the
+    /// orientation and gyro aiming. It is also removed from the raw values given to `Self::on_gyro_x_changed`
+    /// and the other axes
+    pub fn set_gyro_bias(&mut self, bias: GyroBias) {
+        let mut settings = self.settings.lock().unwrap();
+        settings.gyro_bias = bias;
+        if let Some(estimator) = settings.gyro_estimator.as_mut() {
+            estimator.set_bias(bias);
+        }
+    }
+
+    pub fn gyro_bias(&self) -> GyroBias {
+        self.settings.lock().unwrap().gyro_bias
+    }
+
+    /// Keep updating the gyroscope bias whenever the controller rests, `None` to only use the bias given to
+    /// `Self::set_gyro_bias`. Enabled by default
+    pub fn set_gyro_bias_estimator(&mut self, estimator: Option<GyroBiasEstimator>) {
+        let mut settings = self.settings.lock().unwrap();
+        let bias = settings.gyro_bias;
+        settings.gyro_estimator = estimator.map(|mut estimator| {
+            estimator.set_bias(bias);
+            estimator
+        });
+    }
+
+    /// Whether the controller was resting during the last checked window, `false` if the estimator is disabled
+    pub fn is_at_rest(&self) -> bool {
+        self.settings
+            .lock()
+            .unwrap()
+            .gyro_estimator
+            .is_some_and(|estimator| estimator.is_at_rest())
+    }
+
+    /// Orientation of the controller relative to the last `Self::recenter_orientation` call, estimated from the
+    /// gyroscope and accelerometer
+    pub fn orientation(&self) -> Quaternion {
+        self.settings.lock().unwrap().orientation.orientation()
+    }
+
+    /// Yaw, pitch and roll of the controller relative to the last `Self::recenter_orientation` call, in degrees
+    pub fn yaw_pitch_roll(&self) -> (f32, f32, f32) {
+        self.settings.lock().unwrap().orientation.yaw_pitch_roll()
+    }
+
+    /// Direction of gravity in the controller's frame, in g
+    pub fn gravity(&self) -> [f32; 3] {
+        self.settings.lock().unwrap().orientation.gravity()
+    }
+
+    /// Make the current orientation of the controller the neutral one
+    pub fn recenter_orientation(&mut self) {
+        self.settings.lock().unwrap().orientation.recenter();
+    }
+
+    /// Replace the filter estimating the orientation, to tune how fast it corrects the gyroscope drift
+    pub fn set_orientation_filter(&mut self, filter: MahonyFilter) {
+        self.settings.lock().unwrap().orientation = filter;
+    }
+
+    /// Provide a callback to be called with every report's camera movement for gyro aiming, in degrees pointing
+    /// right and up. The flick stick movement, if enabled, is added to the horizontal movement
+    pub fn on_gyro_aim(&mut self, cb: Box<dyn FnMut(f32, f32) + Send>) {
+        self.gyro_aim_callbacks.lock().unwrap().push(cb);
+    }
+
+    /// Configure how the gyroscope readings are turned into camera movement for `Self::on_gyro_aim`
+    pub fn set_gyro_aim(&mut self, gyro_aim: GyroAim) {
+        self.settings.lock().unwrap().gyro_aim = gyro_aim;
+    }
+
+    /// Turn the camera with the right stick for `Self::on_gyro_aim`, `None` to disable it
+    pub fn set_flick_stick(&mut self, flick_stick: Option<FlickStick>) {
+        self.settings.lock().unwrap().flick_stick = flick_stick;
+    }
+
+    /// Provide a callback to be called when the gyroscope X axis is changed, in raw sensor units with the
+    /// gyroscope bias removed. See `Self::on_motion` for readings in degrees/second
     pub fn on_gyro_x_changed<F>(&mut self, cb: &'static F)
     where
         F: Fn(i16) + Send + Sync,
@@ -246,7 +918,8 @@ impl DualSense {
         self.register_i16(InputProperty::GyroscopeX, cb);
     }
 
-    /// Provide a callback to be called when the gyroscope Y axis is changed
+    /// Provide a callback to be called when the gyroscope Y axis is changed, in raw sensor units with the
+    /// gyroscope bias removed. See `Self::on_motion` for readings in degrees/second
     pub fn on_gyro_y_changed<F>(&mut self, cb: &'static F)
     where
         F: Fn(i16) + Send + Sync,
@@ -254,7 +927,8 @@ impl DualSense {
         self.register_i16(InputProperty::GyroscopeY, cb);
     }
 
-    /// Provide a callback to be called when the gyroscope Z axis is changed
+    /// Provide a callback to be called when the gyroscope Z axis is changed, in raw sensor units with the
+    /// gyroscope bias removed. See `Self::on_motion` for readings in degrees/second
     pub fn on_gyro_z_changed<F>(&mut self, cb: &'static F)
     where
         F: Fn(i16) + Send + Sync,
@@ -286,6 +960,76 @@ impl DualSense {
         self.register_i16(InputProperty::AccelerationZ, cb);
     }
 
+    /// Provide a callback to be called when any finger on the touchpad changes, with both fingers read from the
+    /// same report
+    pub fn on_touch(&mut self, mut cb: Box<dyn FnMut(TouchFrame) + Send>) {
+        self.callbacks_v2
+            .lock()
+            .unwrap()
+            .entry(ComboProperty::Touch(TouchFrame::default()))
+            .or_default()
+            .push(Box::new(move |x| cb(x.into())));
+    }
+
+    /// Provide a callback to be called when a finger touches the touchpad, moves on it or is lifted
+    pub fn on_touch_event(&mut self, cb: Box<dyn FnMut(TouchEvent) + Send>) {
+        self.touch_event_callbacks.lock().unwrap().push(cb);
+    }
+
+    /// Provide a callback to be called when a gesture is made on the touchpad
+    pub fn on_touch_gesture(&mut self, cb: Box<dyn FnMut(TouchGesture) + Send>) {
+        self.touch_gesture_callbacks.lock().unwrap().push(cb);
+    }
+
+    /// Configure the thresholds used to recognize the gestures given to `Self::on_touch_gesture` callbacks
+    pub fn set_touch_gesture_recognizer(&mut self, recognizer: TouchGestureRecognizer) {
+        self.settings.lock().unwrap().touch_gestures = recognizer;
+    }
+
+    /// Provide a callback to be called with the mouse input produced by the trackpad mapper, see
+    /// `Self::set_trackpad`
+    pub fn on_mouse(&mut self, cb: Box<dyn FnMut(MouseEvent) + Send>) {
+        self.mouse_callbacks.lock().unwrap().push(cb);
+    }
+
+    /// Use the touchpad as a trackpad producing the events given to `Self::on_mouse` callbacks, `None` to disable
+    /// it. Disabled by default
+    pub fn set_trackpad(&mut self, trackpad: Option<TrackpadMapper>) {
+        self.settings.lock().unwrap().trackpad = trackpad;
+    }
+
+    /// Provide a callback to be called when the touchpad zone with the given id is pressed or released, see
+    /// `Self::set_touch_zones`
+    pub fn on_touch_zone_changed(&mut self, id: u8, cb: Box<dyn FnMut(bool) + Send>) {
+        self.register_button(
+            ComboProperty::TouchZone(ZoneButton { id, pressed: false }),
+            cb,
+        );
+    }
+
+    /// Split the touchpad into zones given to `Self::on_touch_zone_changed` callbacks and combos, replacing the
+    /// previous zones. No zones by default
+    pub fn set_touch_zones(&mut self, zones: TouchZones) {
+        let zone_prop = |id| ComboProperty::TouchZone(ZoneButton { id, pressed: false });
+        let mut callbacks = self.callbacks_v2.lock().unwrap();
+        let mut cache = self.callback_cache_v2.lock().unwrap();
+        // zones tracked only for combos are dropped with their cached state, ids with callbacks stay
+        callbacks.retain(|prop, cbs| {
+            let stale = matches!(prop, ComboProperty::TouchZone(button)
+                if cbs.is_empty() && !zones.zones().iter().any(|zone| zone.id == button.id));
+            if stale {
+                cache.remove(&prop.base());
+            }
+            !stale
+        });
+        // an empty entry is enough for the zone state to be tracked
+        zones.zones().iter().for_each(|zone| {
+            callbacks.entry(zone_prop(zone.id)).or_default();
+        });
+        drop(callbacks);
+        self.settings.lock().unwrap().touch_zones = zones;
+    }
+
     /// Provide a callback to be called when the touchpad is touched
     pub fn on_touchpad1_pressed<F>(&mut self, cb: &'static F)
     where
@@ -352,7 +1096,16 @@ impl DualSense {
         self.register_u16(InputProperty::TouchPad2Y, cb);
     }
 
-    /// Provide a callback to be called when the left stick force trigger is active
+    /// Provide a callback to be called with every report's feedback of the left and right adaptive triggers, to
+    /// react when a `TriggerEffect::Weapon` clicks or a trigger reaches the resistance
+    pub fn on_trigger_feedback(
+        &mut self,
+        cb: Box<dyn FnMut(TriggerFeedbackStatus, TriggerFeedbackStatus) + Send>,
+    ) {
+        self.trigger_feedback_callbacks.lock().unwrap().push(cb);
+    }
+
+    /// Provide a callback to be called when an effect starts or stops running on the left trigger
     pub fn on_left_force_enabled<F>(&mut self, cb: &'static F)
     where
         F: Fn(bool) + Send + Sync,
@@ -360,7 +1113,7 @@ impl DualSense {
         self.register_bool(InputProperty::L2FeedbackOn, cb);
     }
 
-    /// Provide a callback to be called when the right stick force trigger is active
+    /// Provide a callback to be called when an effect starts or stops running on the right trigger
     pub fn on_right_force_enabled<F>(&mut self, cb: &'static F)
     where
         F: Fn(bool) + Send + Sync,
@@ -368,7 +1121,7 @@ impl DualSense {
         self.register_bool(InputProperty::R2FeedbackOn, cb);
     }
 
-    /// Provide a callback to be called when the left stick force amount changed
+    /// Provide a callback to be called when the position where the left trigger effect stops the trigger changed
     pub fn on_left_force_changed<F>(&mut self, cb: &'static F)
     where
         F: Fn(u8) + Send + Sync,
@@ -376,7 +1129,7 @@ impl DualSense {
         self.register_u8(InputProperty::L2FeedbackValue, cb);
     }
 
-    /// Provide a callback to be called when the right stick force trigger is active
+    /// Provide a callback to be called when the position where the right trigger effect stops the trigger changed
     pub fn on_right_force_changed<F>(&mut self, cb: &'static F)
     where
         F: Fn(u8) + Send + Sync,
@@ -393,31 +1146,31 @@ impl DualSense {
             .push(Box::new(move |x| cb(x.into())));
     }
 
-    fn register_pad(&mut self, prop: ComboProperty, mut cb: Box<dyn FnMut(DPad) + Send>) {
+    fn register_symbols(&mut self, prop: ComboProperty, mut cb: Box<dyn FnMut(Symbols) + Send>) {
         self.callbacks_v2
             .lock()
             .unwrap()
             .entry(prop)
             .or_default()
-            .push(Box::new(move |x| cb(x.to_dpad())));
+            .push(Box::new(move |x| cb(x.to_symbols())));
     }
 
-    fn register_symbols(&mut self, prop: ComboProperty, mut cb: Box<dyn FnMut(Symbols) + Send>) {
+    fn register_trigger(&mut self, prop: ComboProperty, mut cb: Box<dyn FnMut(Trigger) + Send>) {
         self.callbacks_v2
             .lock()
             .unwrap()
             .entry(prop)
             .or_default()
-            .push(Box::new(move |x| cb(x.to_symbols())));
+            .push(Box::new(move |x| cb(x.to_trigger())));
     }
 
-    fn register_trigger(&mut self, prop: ComboProperty, mut cb: Box<dyn FnMut(Trigger) + Send>) {
+    fn register_button(&mut self, prop: ComboProperty, mut cb: Box<dyn FnMut(bool) + Send>) {
         self.callbacks_v2
             .lock()
             .unwrap()
             .entry(prop)
             .or_default()
-            .push(Box::new(move |x| cb(x.to_trigger())));
+            .push(Box::new(move |x| cb(x.to_bool())));
     }
 
     fn register_u8<F>(&mut self, prop: InputProperty, cb: &'static F)
@@ -477,14 +1230,35 @@ impl DualSense {
             .push(Box::new(move |x| cb(x.to_bool())));
     }
 
+    fn packet_received(
+        callbacks: &mut HashMap<InputProperty, Vec<CBFunction>>,
+        cache: &mut HashMap<InputProperty, ValueType>,
+        raw_gyro_bias: [i16; 3],
+        data: &[u8; 64],
+    ) {
+        callbacks.iter_mut().for_each(|(prop, cbs)| {
+            let new_val = match (prop.gyro_axis(), Self::extract_bytes(prop, data)) {
+                (Some(axis), ValueType::I16(raw)) => {
+                    ValueType::I16(raw.saturating_sub(raw_gyro_bias[axis]))
+                }
+                (_, value) => value,
+            };
+            if cache.get(prop) != Some(&new_val) {
+                cache.insert(*prop, new_val);
+                cbs.iter_mut().for_each(|cb| cb(new_val));
+            }
+        });
+    }
+
     fn packet_received_v2(
         callbacks: &mut HashMap<ComboProperty, Vec<CBFunction2>>,
         cache: &mut HashMap<ComboProperty, ComboProperty>,
         combos: &mut [Combo],
+        settings: &mut InputSettings,
         data: &[u8; 64],
     ) {
         callbacks.iter_mut().for_each(|(prop, cbs)| {
-            let new_val = Self::extract_bytes_v2(prop, data);
+            let new_val = settings.decode(prop, data);
             let mut update = false;
             if let Some(cached) = cache.get(&prop.base()) {
                 if cached != &new_val {
@@ -517,182 +1291,124 @@ impl DualSense {
         device.write(&data).ok();
     }
 
-    /// Set the trigger effect for the left trigger
-    pub fn set_left_trigger_effect(&mut self, trigger_mode: TriggerEffect) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::LeftEffectMode, trigger_mode.byte());
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Set the trigger effect for the left trigger, the whole effect is sent in the same report
+    pub fn set_left_trigger_effect(
+        &mut self,
+        effect: TriggerEffect,
+    ) -> Result<(), TriggerEffectError> {
+        self.update(|out| {
+            out.set_left_trigger_effect(effect);
+        })
     }
 
-    pub fn set_left_start_of_resistance(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::LeftEffectParameter1, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Set the trigger effect for the right trigger, the whole effect is sent in the same report
+    pub fn set_right_trigger_effect(
+        &mut self,
+        effect: TriggerEffect,
+    ) -> Result<(), TriggerEffectError> {
+        self.update(|out| {
+            out.set_right_trigger_effect(effect);
+        })
     }
 
-    /// Depends on the trigger mode
-    pub fn set_left_param2(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::LeftEffectParameter2, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Set the light corresponding to the player number this controller belongs to (white light
+    /// under the touchpad), see `PlayerLeds::player`
+    pub fn set_player_number(&mut self, value: u8) -> Result<(), PlayerLedsError> {
+        self.set_player_leds(PlayerLeds::player(value)?);
+        Ok(())
     }
 
-    /// Only for mode2: the force exerted
-    pub fn set_left_param3(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::LeftEffectParameter3, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Light any combination of the white LEDs under the touchpad
+    pub fn set_player_leds(&mut self, leds: PlayerLeds) {
+        self.apply_batch(OutputBatch::default().set_player_leds(leds));
     }
 
-    /// Only for mode4|20: the force exerted near release state
-    pub fn set_left_param4(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::LeftEffectParameter4, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Last LEDs given to `Self::set_player_leds`
+    pub fn player_leds(&self) -> PlayerLeds {
+        let light = self.output_value(OutputProperty::PlayerLight);
+        output_batch::player_leds(light)
     }
 
-    /// Only for mode4|20: the force exerted near middle
-    pub fn set_left_param5(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::LeftEffectParameter5, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Set the brightness of the player LEDs. High by default
+    pub fn set_player_leds_brightness(&mut self, brightness: LedBrightness) {
+        self.apply_batch(OutputBatch::default().set_player_leds_brightness(brightness));
     }
 
-    /// Only for mode4|20: the force exerted at pressed state
-    pub fn set_left_param6(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::LeftEffectParameter6, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Whether the player LEDs fade in when turned on instead of lighting up instantly. Enabled by default
+    pub fn set_player_leds_fade(&mut self, fade: bool) {
+        self.apply_batch(OutputBatch::default().set_player_leds_fade(fade));
     }
 
-    /// Only for mode4|20: actuation hertz frequency
-    pub fn set_left_param7(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::LeftEffectParameter7, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Turn the mute button LED on or off, see `Self::set_mute_led`
+    pub fn set_mute(&mut self, value: bool) {
+        self.set_mute_led(if value { MuteLed::On } else { MuteLed::Off });
     }
 
-    /// Set the trigger effect for the right trigger
-    pub fn set_right_trigger_effect(&mut self, trigger_mode: TriggerEffect) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::RightEffectMode, trigger_mode.byte());
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Set the state of the mute button LED. It only changes the LED, see `Self::set_microphone_muted` to mute
+    /// the microphone
+    pub fn set_mute_led(&mut self, state: MuteLed) {
+        self.apply_batch(OutputBatch::default().set_mute_led(state));
     }
 
-    pub fn set_right_start_of_resistance(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::RightEffectParameter1, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Last state given to `Self::set_mute_led`
+    pub fn mute_led(&self) -> MuteLed {
+        MuteLed::from_byte(self.output_value(OutputProperty::Mute))
     }
 
-    /// Depends on the trigger mode
-    pub fn set_right_param2(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::RightEffectParameter2, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Set which features the controller turns off or mutes
+    pub fn set_power_save(&mut self, power_save: PowerSave) {
+        self.apply_batch(OutputBatch::default().set_power_save(power_save));
     }
 
-    /// Only for mode2: the force exerted
-    pub fn set_right_param3(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::RightEffectParameter3, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    /// Last flags given to `Self::set_power_save` or changed by the mute setters
+    pub fn power_save(&self) -> PowerSave {
+        PowerSave::from_byte(self.output_value(OutputProperty::PowerSave))
     }
 
-    /// Only for mode4|20: the force exerted near release state
-    pub fn set_right_param4(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::RightEffectParameter4, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    pub fn set_microphone_muted(&mut self, muted: bool) {
+        let power_save = self.power_save();
+        self.set_power_save(PowerSave {
+            mute_microphone: muted,
+            ..power_save
+        });
     }
 
-    /// Only for mode4|20: the force exerted near middle
-    pub fn set_right_param5(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::RightEffectParameter5, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    pub fn is_microphone_muted(&self) -> bool {
+        self.power_save().mute_microphone
     }
 
-    /// Only for mode4|20: the force exerted at pressed state
-    pub fn set_right_param6(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::RightEffectParameter6, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    pub fn set_speaker_muted(&mut self, muted: bool) {
+        let power_save = self.power_save();
+        self.set_power_save(PowerSave {
+            mute_speaker: muted,
+            ..power_save
+        });
     }
 
-    /// Only for mode4|20: actuation hertz frequency
-    pub fn set_right_param7(&mut self, value: u8) {
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::RightEffectParameter7, value);
-        *self.output_cache_changed.lock().unwrap() = true;
+    pub fn is_speaker_muted(&self) -> bool {
+        self.power_save().mute_speaker
     }
 
-    /// Set the light corresponding to the player number this controller belongs to (white light
-    /// under the touchpad). Allowed range: 0-5. 0 means no light. Led placement:
-    /// - 0: xxxxxxx
-    /// - 1: xxxOxxx
-    /// - 2: xxOxOxx
-    /// - 3: xOxOxOx
-    /// - 4: OxOxOxO
-    /// - 5: xOxxxOx
-    pub fn set_player_number(&mut self, value: u8) {
-        if !(0..=5).contains(&value) {
-            return;
-        }
-        let to_send = match value {
-            0 => 0x00,
-            1 => 0x04,
-            2 => 0x0A,
-            3 => 0x15,
-            4 => 0x1B,
-            5 => 0x10,
-            _ => 0x04,
-        };
-        self.output_cache
-            .lock()
-            .unwrap()
-            .insert(OutputProperty::PlayerLight, to_send);
-        *self.output_cache_changed.lock().unwrap() = true;
+    pub fn set_headphone_muted(&mut self, muted: bool) {
+        let power_save = self.power_save();
+        self.set_power_save(PowerSave {
+            mute_headphone: muted,
+            ..power_save
+        });
     }
 
-    pub fn set_mute(&mut self, value: bool) {
+    pub fn is_headphone_muted(&self) -> bool {
+        self.power_save().mute_headphone
+    }
+
+    /// Value of an output byte, 0 if it was never set
+    fn output_value(&self, property: OutputProperty) -> u8 {
         self.output_cache
             .lock()
             .unwrap()
-            .insert(OutputProperty::Mute, value as u8);
-        *self.output_cache_changed.lock().unwrap() = true;
+            .get(&property)
+            .copied()
+            .unwrap_or_default()
     }
 
     /// Returns an id to unassign this combo in the future
@@ -715,6 +1431,11 @@ impl DualSense {
             ComboProperty::RB(false),
             ComboProperty::LeftPad(AnalogPad::new(0, 0)),
             ComboProperty::RightPad(AnalogPad::new(0, 0)),
+            ComboProperty::LeftPadDirection(DPad::None),
+            ComboProperty::RightPadDirection(DPad::None),
+            ComboProperty::LTPressed(false),
+            ComboProperty::RTPressed(false),
+            ComboProperty::Gesture(Gesture::None),
         ];
         props.iter().for_each(|prop| {
             self.callbacks_v2
@@ -722,10 +1443,36 @@ impl DualSense {
                 .unwrap()
                 .entry(*prop)
                 .or_default()
-                .push(Box::new(move |x| {}));
+                .push(Box::new(move |_| {}));
         })
     }
 
+    fn calibrate_pad(
+        &self,
+        prop: ComboProperty,
+        rest: Duration,
+        rotation: Duration,
+    ) -> StickCalibration {
+        let mut calibrator = StickCalibrator::default();
+        self.sample_cache(prop, rest, |pad| calibrator.sample_rest(&pad.into()));
+        self.sample_cache(prop, rotation, |pad| calibrator.sample_range(&pad.into()));
+        calibrator.finish()
+    }
+
+    /// Periodically read the last value received for a property, for the given duration
+    fn sample_cache<F>(&self, prop: ComboProperty, duration: Duration, mut cb: F)
+    where
+        F: FnMut(ComboProperty),
+    {
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            if let Some(value) = self.callback_cache_v2.lock().unwrap().get(&prop.base()) {
+                cb(*value);
+            }
+            sleep(Duration::from_millis(10));
+        }
+    }
+
     #[allow(dead_code)]
     fn debug_print_packet(data: &[u8; PACKET_SIZE]) {
         data.chunks(8).for_each(|w| {
@@ -737,24 +1484,37 @@ impl DualSense {
         println!()
     }
 
-    fn extract_bytes_v2(prop: &ComboProperty, data: &[u8; 64]) -> ComboProperty {
+    fn extract_bytes(prop: &InputProperty, data: &[u8; 64]) -> ValueType {
         if prop.offset().is_whole_byte() {
             prop.convert(&data.as_slice()[prop.offset().bytes])
         } else if prop.offset().is_single_byte() {
-            let mut out = 0u8;
-            let byte = prop.offset().bytes.start;
-            let val = data.as_slice()[byte];
-
-            for i in prop.offset().bits {
-                let offset = i - prop.offset().bits.start;
-                let current_bit = (val & (1 << i)) >> i;
-                out |= current_bit << offset;
-            }
-            prop.convert(&[out])
+            prop.convert(&[Self::extract_bits(&prop.offset(), data)])
         } else {
             unreachable!()
         }
     }
+
+    pub(crate) fn extract_bytes_v2(prop: &ComboProperty, data: &[u8; 64]) -> ComboProperty {
+        if prop.offset().is_whole_byte() {
+            prop.convert(&data.as_slice()[prop.offset().bytes])
+        } else if prop.offset().is_single_byte() {
+            prop.convert(&[Self::extract_bits(&prop.offset(), data)])
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub(crate) fn extract_bits(offset: &Offset, data: &[u8; 64]) -> u8 {
+        let mut out = 0u8;
+        let val = data.as_slice()[offset.bytes.start];
+
+        for i in offset.bits.clone() {
+            let shift = i - offset.bits.start;
+            let current_bit = (val & (1 << i)) >> i;
+            out |= current_bit << shift;
+        }
+        out
+    }
 }
 
 impl Default for DualSense {
@@ -10,21 +10,21 @@
 //!
 //! Log details about the left and right sticks' positions:
 //!
-//! ```rust
+//! ```rust,no_run
+//! use dualsense_rs::DualSense;
+//!
 //! let mut controller = DualSense::default();
 //!
-//! controller.on_left_pad_x_changed(&|lpx| println!("left pad x: {lpx}"));
-//! controller.on_left_pad_x_changed(&|lpx| {
-//!     if lpx > 127 {
-//!         println!("left pad x in right region: {lpx}")
+//! controller.on_left_pad_changed(Box::new(|lp| println!("left pad: {:?}", lp.normalize())));
+//! controller.on_left_pad_changed(Box::new(|lp| {
+//!     if lp.normalize().0 > 0.0 {
+//!         println!("left pad in right region: {:?}", lp.normalize())
 //!     }
-//! });
-//! controller.on_left_pad_y_changed(&|lpy| println!("left pad y: {lpy}"));
-//! controller.on_right_pad_x_changed(&|rpx| println!("right pad x: {rpx}"));
+//! }));
 //!
 //! let handle = controller.run();
 //! // can also add after the `.run()` call
-//! controller.on_right_pad_y_changed(&|rpy| println!("right pad y: {rpy}"));
+//! controller.on_right_pad_changed(Box::new(|rp| println!("right pad: {:?}", rp.normalize())));
 //! // make sure to join the thread so the program doesn't stop immediately
 //! handle.join().ok();
 //! ```
@@ -32,11 +32,15 @@
 //! ### Write
 //!
 //! Output values are stored in an internal cache and will be sent in the following read/write cycle. Packets will
-//! not be sent if nothing changed.
+//! not be sent if nothing changed. Use `DualSense::update` to send several changes in the same packet.
+//!
+//! ```rust,no_run
+//! use dualsense_rs::{properties::trigger_effect::TriggerEffect, DualSense};
 //!
-//! ```rust
 //! let mut controller = DualSense::default();
-//! controller.set_left_trigger_effect(TriggerEffect::Mode1);
+//! controller
+//!     .set_left_trigger_effect(TriggerEffect::Feedback { start: 2, strength: 6 })
+//!     .unwrap();
 //!
 //! let handle = controller.run();
 //! controller.set_light_red(255);